@@ -0,0 +1,287 @@
+use crate::Acceleration;
+
+/// Deviation from 1g in milli-g for an axis to be considered pointing up or down.
+const TILT_TOLERANCE: i16 = 200;
+/// Acceleration in milli-g on an axis that counts towards a shake.
+const SHAKE_TOLERANCE: i16 = 400;
+/// Number of direction changes within `SHAKE_RTX` samples that make a shake.
+const SHAKE_COUNT_THRESHOLD: u8 = 4;
+/// Number of samples a shake lasts.
+const SHAKE_DAMPING: u8 = 10;
+/// Number of samples after which direction changes are forgotten.
+const SHAKE_RTX: u8 = 30;
+/// Force below which the micro:bit is in freefall, 0.4g.
+const FREEFALL_TOLERANCE: u32 = 400;
+/// Number of identical samples before a gesture is reported.
+const GESTURE_DAMPING: u8 = 5;
+
+/// Gestures detected by the micro:bit runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    LogoUp,
+    LogoDown,
+    TiltLeft,
+    TiltRight,
+    FaceUp,
+    FaceDown,
+    FreeFall,
+    /// A force above 3g.
+    ThreeG,
+    /// A force above 6g. Requires the ±8g range, see `GestureDetector`.
+    SixG,
+    /// A force above 8g. Requires the ±8g range, see `GestureDetector`.
+    EightG,
+    Shake,
+}
+
+#[derive(Default)]
+struct Shake {
+    x: bool,
+    y: bool,
+    z: bool,
+    count: u8,
+    timer: u8,
+    shaken: u8,
+}
+
+/// Classifies accelerometer samples into gestures, using the algorithm of the micro:bit runtime.
+///
+/// Samples should be provided at a steady rate, the thresholds are tuned for 50 Hz.
+///
+/// Forces can only be detected up to the range of the accelerometer. In the default ±2g range the
+/// strongest possible force is about 3.5g, so `SixG` and `EightG` are never reported. Like the
+/// micro:bit runtime, switch to `Range::G8` with `Mma8653::set_range` to detect shocks.
+///
+/// ```
+/// use drogue_microbit_accelerometer::{Acceleration, Gesture, GestureDetector};
+///
+/// let mut detector = GestureDetector::new();
+/// let flat = Acceleration::new(0, 0, -1000);
+/// let gesture = (0..10).filter_map(|_| detector.update(flat)).next();
+/// assert_eq!(gesture, Some(Gesture::FaceUp));
+/// ```
+#[derive(Default)]
+pub struct GestureDetector {
+    shake: Shake,
+    candidate: Option<Gesture>,
+    sigma: u8,
+    current: Option<Gesture>,
+}
+
+impl GestureDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The gesture of the micro:bit, if any.
+    pub fn current(&self) -> Option<Gesture> {
+        self.current
+    }
+
+    /// Classify a sample, returning a gesture when it is detected. Orientations and forces have
+    /// to persist for a few samples before they are reported, a shake is reported right away.
+    pub fn update(&mut self, accel: Acceleration) -> Option<Gesture> {
+        let gesture = self.posture(accel);
+        if gesture == Some(Gesture::Shake) {
+            if self.current == Some(Gesture::Shake) {
+                return None;
+            }
+            self.current = gesture;
+            return gesture;
+        }
+
+        if gesture == self.candidate {
+            if self.sigma < GESTURE_DAMPING {
+                self.sigma += 1;
+            }
+        } else {
+            self.candidate = gesture;
+            self.sigma = 0;
+        }
+
+        if self.candidate != self.current && self.sigma >= GESTURE_DAMPING {
+            self.current = self.candidate;
+            return self.current;
+        }
+        None
+    }
+
+    fn posture(&mut self, accel: Acceleration) -> Option<Gesture> {
+        let force = accel.force_squared();
+        let shake = &mut self.shake;
+
+        // Count changes of direction of strong accelerations on any axis
+        let shake_detected = reversed(accel.x, &mut shake.x)
+            | reversed(accel.y, &mut shake.y)
+            | reversed(accel.z, &mut shake.z);
+
+        if shake_detected && shake.count < SHAKE_COUNT_THRESHOLD {
+            shake.count += 1;
+            if shake.count == 1 {
+                shake.timer = 0;
+            }
+            if shake.count == SHAKE_COUNT_THRESHOLD {
+                shake.shaken = SHAKE_DAMPING;
+            }
+        }
+
+        if shake.count > 0 {
+            shake.timer += 1;
+            if shake.shaken > 0 {
+                shake.shaken -= 1;
+                if shake.shaken == 0 {
+                    shake.count = 0;
+                }
+            } else if shake.timer >= SHAKE_RTX {
+                shake.count = 0;
+            }
+        }
+
+        if shake.shaken > 0 {
+            return Some(Gesture::Shake);
+        }
+
+        if force < FREEFALL_TOLERANCE * FREEFALL_TOLERANCE {
+            return Some(Gesture::FreeFall);
+        }
+        if force > 8192 * 8192 {
+            return Some(Gesture::EightG);
+        }
+        if force > 6144 * 6144 {
+            return Some(Gesture::SixG);
+        }
+        if force > 3072 * 3072 {
+            return Some(Gesture::ThreeG);
+        }
+
+        let low = -1000 + TILT_TOLERANCE;
+        let high = 1000 - TILT_TOLERANCE;
+        if accel.x < low {
+            Some(Gesture::TiltLeft)
+        } else if accel.x > high {
+            Some(Gesture::TiltRight)
+        } else if accel.y < low {
+            Some(Gesture::LogoDown)
+        } else if accel.y > high {
+            Some(Gesture::LogoUp)
+        } else if accel.z < low {
+            Some(Gesture::FaceUp)
+        } else if accel.z > high {
+            Some(Gesture::FaceDown)
+        } else {
+            None
+        }
+    }
+}
+
+/// Track the direction of strong accelerations, returning true if it reversed.
+fn reversed(value: i16, negative: &mut bool) -> bool {
+    if value.abs() > SHAKE_TOLERANCE && (value < 0) != *negative {
+        *negative = value < 0;
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(
+        detector: &mut GestureDetector,
+        accel: Acceleration,
+        samples: usize,
+    ) -> Option<Gesture> {
+        let mut gesture = None;
+        for _ in 0..samples {
+            if let Some(g) = detector.update(accel) {
+                assert!(gesture.is_none());
+                gesture = Some(g);
+            }
+        }
+        gesture
+    }
+
+    #[test]
+    fn orientation() {
+        let mut detector = GestureDetector::new();
+        let flat = Acceleration::new(0, 0, -1000);
+        assert_eq!(feed(&mut detector, flat, 5), None);
+        assert_eq!(feed(&mut detector, flat, 1), Some(Gesture::FaceUp));
+        assert_eq!(feed(&mut detector, flat, 10), None);
+
+        // Short glitches are ignored
+        let left = Acceleration::new(-900, 0, -300);
+        assert_eq!(feed(&mut detector, left, 3), None);
+        assert_eq!(feed(&mut detector, flat, 10), None);
+
+        assert_eq!(feed(&mut detector, left, 10), Some(Gesture::TiltLeft));
+        let up = Acceleration::new(0, 1000, 0);
+        assert_eq!(feed(&mut detector, up, 10), Some(Gesture::LogoUp));
+        let down = Acceleration::new(0, 0, 1000);
+        assert_eq!(feed(&mut detector, down, 10), Some(Gesture::FaceDown));
+        assert_eq!(detector.current(), Some(Gesture::FaceDown));
+    }
+
+    #[test]
+    fn forces() {
+        let mut detector = GestureDetector::new();
+        let falling = Acceleration::new(100, -50, 200);
+        assert_eq!(feed(&mut detector, falling, 10), Some(Gesture::FreeFall));
+        let bump = Acceleration::new(0, 2500, -2500);
+        assert_eq!(feed(&mut detector, bump, 10), Some(Gesture::ThreeG));
+        let hit = Acceleration::new(5000, 5000, 0);
+        assert_eq!(feed(&mut detector, hit, 10), Some(Gesture::SixG));
+        let crash = Acceleration::new(8000, 0, -8000);
+        assert_eq!(feed(&mut detector, crash, 10), Some(Gesture::EightG));
+    }
+
+    #[test]
+    fn forces_are_limited_by_range() {
+        // Every axis saturated in the ±2g range is about 3.5g
+        let mut detector = GestureDetector::new();
+        for &accel in &[
+            Acceleration::new(1996, 1996, 1996),
+            Acceleration::new(-2000, -2000, -2000),
+            Acceleration::new(1996, -2000, 1996),
+        ] {
+            for _ in 0..10 {
+                let gesture = detector.update(accel);
+                assert_ne!(gesture, Some(Gesture::SixG));
+                assert_ne!(gesture, Some(Gesture::EightG));
+            }
+            assert_eq!(detector.current(), Some(Gesture::ThreeG));
+        }
+    }
+
+    #[test]
+    fn shake() {
+        let mut detector = GestureDetector::new();
+        let flat = Acceleration::new(0, 0, -1000);
+        feed(&mut detector, flat, 40);
+
+        let left = Acceleration::new(-1500, 0, -1000);
+        let right = Acceleration::new(1500, 0, -1000);
+        assert_eq!(detector.update(left), None);
+        assert_eq!(detector.update(right), None);
+        assert_eq!(detector.update(left), None);
+        assert_eq!(detector.update(right), Some(Gesture::Shake));
+        assert_eq!(detector.update(left), None);
+        assert_eq!(detector.current(), Some(Gesture::Shake));
+
+        // Settles after the shake
+        assert_eq!(feed(&mut detector, flat, 20), Some(Gesture::FaceUp));
+    }
+
+    #[test]
+    fn slow_movement_is_not_a_shake() {
+        let mut detector = GestureDetector::new();
+        let left = Acceleration::new(-1000, 0, 0);
+        let right = Acceleration::new(1000, 0, 0);
+        for _ in 0..4 {
+            assert_ne!(feed(&mut detector, left, 40), Some(Gesture::Shake));
+            assert_ne!(feed(&mut detector, right, 40), Some(Gesture::Shake));
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Driver for the MMA8653 accelerometer of the micro:bit v1, with detection of the gestures of the
+//! micro:bit runtime.
+#![no_std]
+
+mod gesture;
+mod mma8653;
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+
+pub use gesture::{Gesture, GestureDetector};
+pub use mma8653::{DataRate, Error, Interrupts, Mma8653, Range, ADDRESS};
+
+/// Acceleration on each axis in milli-g.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Acceleration {
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+}
+
+impl Acceleration {
+    pub const fn new(x: i16, y: i16, z: i16) -> Self {
+        Self { x, y, z }
+    }
+
+    /// The square of the magnitude in milli-g.
+    pub fn force_squared(&self) -> u32 {
+        let square = |v: i16| (i32::from(v) * i32::from(v)) as u32;
+        square(self.x) + square(self.y) + square(self.z)
+    }
+}
@@ -0,0 +1,331 @@
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+use crate::Acceleration;
+
+/// The I2C address of the MMA8653 on the micro:bit.
+pub const ADDRESS: u8 = 0x1D;
+
+/// Value of the WHO_AM_I register of the MMA8653.
+const DEVICE_ID: u8 = 0x5A;
+
+const STATUS: u8 = 0x00;
+const OUT_X_MSB: u8 = 0x01;
+const INT_SOURCE: u8 = 0x0C;
+const WHO_AM_I: u8 = 0x0D;
+const XYZ_DATA_CFG: u8 = 0x0E;
+const FF_MT_CFG: u8 = 0x15;
+const FF_MT_SRC: u8 = 0x16;
+const FF_MT_THS: u8 = 0x17;
+const FF_MT_COUNT: u8 = 0x18;
+const CTRL_REG1: u8 = 0x2A;
+const CTRL_REG4: u8 = 0x2D;
+const CTRL_REG5: u8 = 0x2E;
+
+const STATUS_ZYXDR: u8 = 1 << 3;
+const CTRL_REG1_ACTIVE: u8 = 1 << 0;
+const INT_DRDY: u8 = 1 << 0;
+const INT_FF_MT: u8 = 1 << 2;
+const FF_MT_CFG_ELE: u8 = 1 << 7;
+const FF_MT_CFG_XYZ: u8 = 0b111 << 3;
+
+/// Resolution of the freefall threshold in milli-g.
+const FF_MT_THS_MG: u16 = 63;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// Communication with the device failed.
+    I2c(E),
+    /// The WHO_AM_I register did not identify a MMA8653.
+    UnknownDevice(u8),
+}
+
+/// Full scale range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Range {
+    G2 = 0,
+    G4 = 1,
+    G8 = 2,
+}
+
+impl Range {
+    /// Counts per g of the 10-bit samples.
+    fn counts_per_g(self) -> i32 {
+        256 >> self as u8
+    }
+}
+
+/// Output data rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataRate {
+    Hz800 = 0,
+    Hz400 = 1,
+    Hz200 = 2,
+    Hz100 = 3,
+    Hz50 = 4,
+    Hz12_5 = 5,
+    Hz6_25 = 6,
+    Hz1_56 = 7,
+}
+
+/// Interrupts of the accelerometer, which are routed to the INT1 pin (P0.28 on the micro:bit).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Interrupts {
+    /// A new sample is available.
+    pub data_ready: bool,
+    /// The acceleration on all axes has been below the freefall threshold.
+    pub freefall: bool,
+}
+
+impl Interrupts {
+    fn bits(&self) -> u8 {
+        let mut bits = 0;
+        if self.data_ready {
+            bits |= INT_DRDY;
+        }
+        if self.freefall {
+            bits |= INT_FF_MT;
+        }
+        bits
+    }
+
+    fn from_bits(bits: u8) -> Self {
+        Self {
+            data_ready: bits & INT_DRDY != 0,
+            freefall: bits & INT_FF_MT != 0,
+        }
+    }
+}
+
+/// Driver for the MMA8653FC 3-axis accelerometer of the micro:bit v1.
+///
+/// Samples are 10 bits, so the resolution is 3.9 mg in the ±2g range, 7.8 mg in the ±4g range and
+/// 15.6 mg in the ±8g range.
+pub struct Mma8653<I2C> {
+    i2c: I2C,
+    range: Range,
+    ctrl_reg1: u8,
+}
+
+impl<I2C, E> Mma8653<I2C>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Verify the device and start sampling at 50 Hz in the ±2g range.
+    ///
+    /// The ±2g range gives the best resolution for orientation gestures, but shocks of 6g and 8g
+    /// need `Range::G8`.
+    pub fn new(i2c: I2C) -> Result<Self, Error<E>> {
+        let mut mma = Self {
+            i2c,
+            range: Range::G2,
+            ctrl_reg1: 0,
+        };
+        let id = mma.read_register(WHO_AM_I)?;
+        if id != DEVICE_ID {
+            return Err(Error::UnknownDevice(id));
+        }
+        mma.write_register(CTRL_REG1, 0)?;
+        mma.write_register(XYZ_DATA_CFG, mma.range as u8)?;
+        mma.ctrl_reg1 = (DataRate::Hz50 as u8) << 3 | CTRL_REG1_ACTIVE;
+        mma.write_register(CTRL_REG1, mma.ctrl_reg1)?;
+        Ok(mma)
+    }
+
+    pub fn range(&self) -> Range {
+        self.range
+    }
+
+    pub fn set_range(&mut self, range: Range) -> Result<(), Error<E>> {
+        self.standby(|mma| mma.write_register(XYZ_DATA_CFG, range as u8))?;
+        self.range = range;
+        Ok(())
+    }
+
+    pub fn set_data_rate(&mut self, rate: DataRate) -> Result<(), Error<E>> {
+        self.ctrl_reg1 = (self.ctrl_reg1 & !(0b111 << 3)) | (rate as u8) << 3;
+        self.standby(|_| Ok(()))
+    }
+
+    /// Enable interrupts on the INT1 pin, which is active low.
+    pub fn enable_interrupts(&mut self, interrupts: Interrupts) -> Result<(), Error<E>> {
+        let bits = interrupts.bits();
+        self.standby(|mma| {
+            mma.write_register(CTRL_REG5, bits)?;
+            mma.write_register(CTRL_REG4, bits)
+        })
+    }
+
+    /// Configure freefall detection, which triggers when the acceleration on all axes has been
+    /// below `threshold_mg` for `samples` consecutive samples. The threshold has a resolution of
+    /// 63 mg, and the event is latched until `interrupt_source` is read.
+    pub fn set_freefall(&mut self, threshold_mg: u16, samples: u8) -> Result<(), Error<E>> {
+        let threshold = core::cmp::min(threshold_mg / FF_MT_THS_MG, 0x7F) as u8;
+        self.standby(|mma| {
+            mma.write_register(FF_MT_CFG, FF_MT_CFG_ELE | FF_MT_CFG_XYZ)?;
+            mma.write_register(FF_MT_THS, threshold)?;
+            mma.write_register(FF_MT_COUNT, samples)
+        })
+    }
+
+    /// The pending interrupts. A freefall event is cleared by this.
+    pub fn interrupt_source(&mut self) -> Result<Interrupts, Error<E>> {
+        let source = Interrupts::from_bits(self.read_register(INT_SOURCE)?);
+        if source.freefall {
+            self.read_register(FF_MT_SRC)?;
+        }
+        Ok(source)
+    }
+
+    /// Returns true if a new sample is available on all axes.
+    pub fn is_data_ready(&mut self) -> Result<bool, Error<E>> {
+        Ok(self.read_register(STATUS)? & STATUS_ZYXDR != 0)
+    }
+
+    /// The latest sample of each axis of the sensor in counts, from -512 to 511.
+    pub fn accel_raw(&mut self) -> Result<[i16; 3], Error<E>> {
+        let mut data = [0; 6];
+        self.i2c
+            .write_read(ADDRESS, &[OUT_X_MSB], &mut data)
+            .map_err(Error::I2c)?;
+        // Samples are left aligned in two registers
+        let axis = |i: usize| i16::from_be_bytes([data[i], data[i + 1]]) >> 6;
+        Ok([axis(0), axis(2), axis(4)])
+    }
+
+    /// The latest sample in milli-g, in the coordinate system of the micro:bit runtime where the x
+    /// and y axes of the sensor are inverted.
+    pub fn accel(&mut self) -> Result<Acceleration, Error<E>> {
+        let raw = self.accel_raw()?;
+        let counts_per_g = self.range.counts_per_g();
+        let mg = |counts: i16| (i32::from(counts) * 1000 / counts_per_g) as i16;
+        Ok(Acceleration::new(-mg(raw[0]), -mg(raw[1]), mg(raw[2])))
+    }
+
+    pub fn free(self) -> I2C {
+        self.i2c
+    }
+
+    /// Run `f` with the device in standby, which is required to change its configuration, and
+    /// then write `ctrl_reg1` to resume sampling.
+    fn standby<F>(&mut self, f: F) -> Result<(), Error<E>>
+    where
+        F: FnOnce(&mut Self) -> Result<(), Error<E>>,
+    {
+        self.write_register(CTRL_REG1, self.ctrl_reg1 & !CTRL_REG1_ACTIVE)?;
+        f(self)?;
+        self.write_register(CTRL_REG1, self.ctrl_reg1)
+    }
+
+    fn read_register(&mut self, register: u8) -> Result<u8, Error<E>> {
+        let mut value = [0];
+        self.i2c
+            .write_read(ADDRESS, &[register], &mut value)
+            .map_err(Error::I2c)?;
+        Ok(value[0])
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) -> Result<(), Error<E>> {
+        self.i2c
+            .write(ADDRESS, &[register, value])
+            .map_err(Error::I2c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockI2c;
+
+    fn device() -> MockI2c {
+        let mut i2c = MockI2c::new(ADDRESS);
+        i2c.registers[WHO_AM_I as usize] = DEVICE_ID;
+        i2c
+    }
+
+    #[test]
+    fn init() {
+        let mma = Mma8653::new(device()).unwrap();
+        let i2c = mma.free();
+        assert_eq!(
+            i2c.writes,
+            [(CTRL_REG1, 0x00), (XYZ_DATA_CFG, 0x00), (CTRL_REG1, 0x21)]
+        );
+
+        let mut i2c = device();
+        i2c.registers[WHO_AM_I as usize] = 0xC4;
+        assert_eq!(
+            Mma8653::new(i2c)
+                .err()
+                .map(|e| e == Error::UnknownDevice(0xC4)),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn configuration() {
+        let mut mma = Mma8653::new(device()).unwrap();
+        mma.i2c.writes.clear();
+        mma.set_range(Range::G8).unwrap();
+        mma.set_data_rate(DataRate::Hz100).unwrap();
+        mma.enable_interrupts(Interrupts {
+            data_ready: true,
+            freefall: true,
+        })
+        .unwrap();
+        mma.set_freefall(400, 4).unwrap();
+        assert_eq!(
+            mma.i2c.writes,
+            [
+                (CTRL_REG1, 0x20),
+                (XYZ_DATA_CFG, 0x02),
+                (CTRL_REG1, 0x21),
+                (CTRL_REG1, 0x18),
+                (CTRL_REG1, 0x19),
+                (CTRL_REG1, 0x18),
+                (CTRL_REG5, 0x05),
+                (CTRL_REG4, 0x05),
+                (CTRL_REG1, 0x19),
+                (CTRL_REG1, 0x18),
+                (FF_MT_CFG, 0xB8),
+                (FF_MT_THS, 6),
+                (FF_MT_COUNT, 4),
+                (CTRL_REG1, 0x19),
+            ]
+        );
+        assert_eq!(mma.range(), Range::G8);
+    }
+
+    #[test]
+    fn samples() {
+        let mut mma = Mma8653::new(device()).unwrap();
+        // x = 1g, y = -0.5g, z = -1.5g at ±2g, left aligned
+        let samples = [256i16, -128, -384];
+        for (i, sample) in samples.iter().enumerate() {
+            let bytes = (sample << 6).to_be_bytes();
+            mma.i2c.registers[1 + 2 * i] = bytes[0];
+            mma.i2c.registers[2 + 2 * i] = bytes[1];
+        }
+        assert_eq!(mma.accel_raw().unwrap(), samples);
+        assert_eq!(mma.accel().unwrap(), Acceleration::new(-1000, 500, -1500));
+
+        mma.set_range(Range::G8).unwrap();
+        assert_eq!(mma.accel().unwrap(), Acceleration::new(-4000, 2000, -6000));
+
+        assert!(!mma.is_data_ready().unwrap());
+        mma.i2c.registers[STATUS as usize] = 0x0F;
+        assert!(mma.is_data_ready().unwrap());
+    }
+
+    #[test]
+    fn interrupts() {
+        let mut mma = Mma8653::new(device()).unwrap();
+        mma.i2c.registers[INT_SOURCE as usize] = 0x04;
+        assert_eq!(
+            mma.interrupt_source().unwrap(),
+            Interrupts {
+                data_ready: false,
+                freefall: true,
+            }
+        );
+    }
+}
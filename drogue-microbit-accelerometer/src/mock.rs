@@ -0,0 +1,59 @@
+//! Mock I2C bus for testing drivers of micro:bit I2C devices on the host. Enable the `mock` feature
+//! to use it from the tests of other crates.
+
+extern crate std;
+
+use std::vec::Vec;
+
+use core::convert::Infallible;
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+/// An I2C device with a register file, where reads and writes auto-increment the register
+/// address like on the MMA8653 and MAG3110. Every register write is recorded.
+pub struct MockI2c {
+    pub address: u8,
+    pub registers: [u8; 0x40],
+    pub writes: Vec<(u8, u8)>,
+}
+
+impl MockI2c {
+    pub fn new(address: u8) -> Self {
+        Self {
+            address,
+            registers: [0; 0x40],
+            writes: Vec::new(),
+        }
+    }
+}
+
+impl Write for MockI2c {
+    type Error = Infallible;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Infallible> {
+        assert_eq!(address, self.address);
+        let register = bytes[0];
+        for (i, value) in bytes[1..].iter().enumerate() {
+            let register = register + i as u8;
+            self.registers[register as usize] = *value;
+            self.writes.push((register, *value));
+        }
+        Ok(())
+    }
+}
+
+impl WriteRead for MockI2c {
+    type Error = Infallible;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Infallible> {
+        assert_eq!(address, self.address);
+        assert_eq!(bytes.len(), 1);
+        let register = bytes[0] as usize;
+        buffer.copy_from_slice(&self.registers[register..register + buffer.len()]);
+        Ok(())
+    }
+}
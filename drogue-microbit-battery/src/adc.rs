@@ -0,0 +1,40 @@
+use nrf51_hal::pac::ADC;
+
+/// Supply voltage measurement with the ADC, using the internal 1.2 V band gap reference and one
+/// third prescaling of VDD.
+pub struct SupplyVoltage {
+    adc: ADC,
+}
+
+impl SupplyVoltage {
+    pub fn new(adc: ADC) -> Self {
+        adc.config.write(|w| {
+            w.res()
+                ._10bit()
+                .inpsel()
+                .supply_one_third_prescaling()
+                .refsel()
+                .vbg()
+        });
+        Self { adc }
+    }
+
+    /// Measure VDD in millivolts. The ADC is only enabled during the conversion, which takes
+    /// less than 70 microseconds.
+    pub fn measure_millivolts(&mut self) -> u16 {
+        self.adc.enable.write(|w| w.enable().enabled());
+        self.adc.events_end.reset();
+        self.adc.tasks_start.write(|w| unsafe { w.bits(1) });
+        while self.adc.events_end.read().bits() == 0 {}
+        self.adc.events_end.reset();
+        let result = u32::from(self.adc.result.read().result().bits());
+        self.adc.enable.write(|w| w.enable().disabled());
+
+        // Full scale of 1023 is 1200 mV at the input, which is VDD / 3
+        (result * 3 * 1200 / 1023) as u16
+    }
+
+    pub fn free(self) -> ADC {
+        self.adc
+    }
+}
@@ -0,0 +1,118 @@
+/// A point on a discharge curve: the battery voltage in millivolts and the remaining charge in
+/// percent at that voltage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+    pub millivolts: u16,
+    pub percent: u8,
+}
+
+impl Point {
+    pub const fn new(millivolts: u16, percent: u8) -> Self {
+        Self {
+            millivolts,
+            percent,
+        }
+    }
+}
+
+/// Maps a battery voltage to a battery level by interpolating linearly between points.
+/// Voltages outside of the curve are reported as the level of the nearest end point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DischargeCurve<'a> {
+    points: &'a [Point],
+}
+
+impl<'a> DischargeCurve<'a> {
+    /// Discharge curve of two alkaline AAA cells in series, from 3.0 V when fresh down to 2.0 V
+    /// when the micro:bit stops working reliably.
+    pub const ALKALINE_2XAAA: DischargeCurve<'static> = DischargeCurve {
+        points: &[
+            Point::new(2000, 0),
+            Point::new(2200, 5),
+            Point::new(2400, 20),
+            Point::new(2500, 35),
+            Point::new(2600, 50),
+            Point::new(2700, 70),
+            Point::new(2800, 85),
+            Point::new(2900, 95),
+            Point::new(3000, 100),
+        ],
+    };
+
+    /// Create a curve from points in order of increasing voltage. Returns `None` if there are no
+    /// points, if the voltages are not increasing, if the levels are decreasing or if a level is
+    /// above 100 percent.
+    pub fn new(points: &'a [Point]) -> Option<Self> {
+        let last = points.last()?;
+        let ordered = points.windows(2).all(|pair| {
+            pair[0].millivolts < pair[1].millivolts && pair[0].percent <= pair[1].percent
+        });
+        if ordered && last.percent <= 100 {
+            Some(Self { points })
+        } else {
+            None
+        }
+    }
+
+    /// The battery level in percent at `millivolts`.
+    pub fn level(&self, millivolts: u16) -> u8 {
+        let first = self.points[0];
+        if millivolts <= first.millivolts {
+            return first.percent;
+        }
+        for pair in self.points.windows(2) {
+            let (low, high) = (pair[0], pair[1]);
+            if millivolts <= high.millivolts {
+                let span = u32::from(high.millivolts - low.millivolts);
+                let offset = u32::from(millivolts - low.millivolts);
+                let delta = u32::from(high.percent - low.percent);
+                // Round to the nearest percent
+                return low.percent + ((offset * delta + span / 2) / span) as u8;
+            }
+        }
+        self.points[self.points.len() - 1].percent
+    }
+}
+
+impl Default for DischargeCurve<'static> {
+    fn default() -> Self {
+        Self::ALKALINE_2XAAA
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolation() {
+        let curve = DischargeCurve::ALKALINE_2XAAA;
+        assert_eq!(curve.level(3300), 100);
+        assert_eq!(curve.level(3000), 100);
+        assert_eq!(curve.level(2950), 98);
+        assert_eq!(curve.level(2600), 50);
+        assert_eq!(curve.level(2550), 43);
+        assert_eq!(curve.level(2100), 3);
+        assert_eq!(curve.level(2000), 0);
+        assert_eq!(curve.level(1500), 0);
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(DischargeCurve::new(&[]), None);
+        assert_eq!(
+            DischargeCurve::new(&[Point::new(2000, 0), Point::new(2000, 100)]),
+            None
+        );
+        assert_eq!(
+            DischargeCurve::new(&[Point::new(2000, 50), Point::new(3000, 10)]),
+            None
+        );
+        assert_eq!(DischargeCurve::new(&[Point::new(3000, 101)]), None);
+
+        let points = [Point::new(1800, 0), Point::new(3200, 100)];
+        let curve = DischargeCurve::new(&points).unwrap();
+        assert_eq!(curve.level(2500), 50);
+        assert_eq!(curve.level(1000), 0);
+    }
+}
@@ -0,0 +1,175 @@
+#![no_std]
+
+use drogue_microbit_gatt::{impl_provider, AttributeId, GattTable, Properties};
+use heapless::consts;
+use rubble::att::{AttributeProvider, Handle};
+use rubble::uuid::Uuid16;
+use rubble::Error;
+
+#[cfg(feature = "v1")]
+mod adc;
+mod curve;
+
+#[cfg(feature = "v1")]
+pub use adc::SupplyVoltage;
+pub use curve::{DischargeCurve, Point};
+pub use drogue_microbit_gatt::Notification;
+
+pub const BAS_UUID: Uuid16 = Uuid16(0x180F);
+const BATTERY_LEVEL_UUID: Uuid16 = Uuid16(0x2A19);
+
+/// An `AttributeProvider` that will enumerate as a Battery Service.
+///
+/// The battery level is derived from the battery voltage with a `DischargeCurve`. When the level
+/// changes and the client has enabled notifications, a notification is queued which can be
+/// retrieved with `take_notification`.
+pub struct BatteryService {
+    table: GattTable<consts::U4>,
+    level_id: AttributeId,
+    curve: DischargeCurve<'static>,
+    notification_pending: bool,
+}
+
+impl BatteryService {
+    /// Create the service with a battery level of 0 until the first measurement.
+    pub fn new(curve: DischargeCurve<'static>) -> Self {
+        let mut table = GattTable::builder();
+        table.service(BAS_UUID).unwrap();
+        let level_id = table
+            .characteristic(
+                BATTERY_LEVEL_UUID,
+                Properties::READ | Properties::NOTIFY,
+                &[0],
+            )
+            .unwrap();
+        Self {
+            table: table.build(),
+            level_id,
+            curve,
+            notification_pending: false,
+        }
+    }
+
+    /// The battery level in percent.
+    pub fn level(&self) -> u8 {
+        self.table.value(self.level_id)[0]
+    }
+
+    /// Set the battery level in percent, saturating at 100.
+    pub fn set_level(&mut self, percent: u8) {
+        let percent = core::cmp::min(percent, 100);
+        if percent != self.level() {
+            self.table.set_value(self.level_id, &[percent]).unwrap();
+            if self.table.notifications_enabled(self.level_id) {
+                self.notification_pending = true;
+            }
+        }
+    }
+
+    /// Set the battery level from a voltage measurement, i.e. from `SupplyVoltage`.
+    pub fn set_voltage(&mut self, millivolts: u16) {
+        self.set_level(self.curve.level(millivolts));
+    }
+
+    /// The handle of the Battery Level value.
+    pub fn value_handle(&self) -> Handle {
+        self.table.handle(self.level_id)
+    }
+
+    /// Take the notification queued by a change of the battery level, if any.
+    pub fn take_notification(&mut self) -> Option<Notification> {
+        if self.notification_pending {
+            self.notification_pending = false;
+            Some(self.table.notification(self.level_id))
+        } else {
+            None
+        }
+    }
+
+    fn write(&mut self, handle: Handle, data: &[u8]) -> Result<(), Error> {
+        self.table.write_attr(handle, data)?;
+        if !self.table.notifications_enabled(self.level_id) {
+            self.notification_pending = false;
+        }
+        Ok(())
+    }
+}
+
+impl Default for BatteryService {
+    fn default() -> Self {
+        Self::new(DischargeCurve::default())
+    }
+}
+
+impl_provider!(BatteryService, write = write);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drogue_microbit_gatt::GattProvider;
+    use rubble::att::HandleRange;
+
+    const LEVEL_HANDLE: u16 = 3;
+    const CCCD_HANDLE: u16 = 4;
+
+    fn read(bas: &mut BatteryService, handle: u16) -> heapless::Vec<u8, consts::U8> {
+        let mut value = heapless::Vec::new();
+        let handle = Handle::from_raw(handle);
+        bas.for_attrs_in_range(HandleRange::new(handle, handle), |_, attr| {
+            value.extend_from_slice(attr.value.as_slice()).unwrap();
+            Ok(())
+        })
+        .unwrap();
+        value
+    }
+
+    #[test]
+    fn attributes() {
+        let mut bas = BatteryService::default();
+        assert_eq!(&read(&mut bas, 1)[..], &[0x0F, 0x18]);
+        assert_eq!(&read(&mut bas, 2)[..], &[0x12, 0x03, 0x00, 0x19, 0x2A]);
+        assert_eq!(&read(&mut bas, LEVEL_HANDLE)[..], &[0]);
+        assert_eq!(&read(&mut bas, CCCD_HANDLE)[..], &[0x00, 0x00]);
+        assert_eq!(bas.last_handle(), CCCD_HANDLE);
+        assert_eq!(bas.value_handle(), Handle::from_raw(LEVEL_HANDLE));
+        assert_eq!(
+            bas.write_attr(Handle::from_raw(LEVEL_HANDLE), &[50]),
+            Err(Error::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn levels() {
+        let mut bas = BatteryService::default();
+        bas.set_voltage(2600);
+        assert_eq!(bas.level(), 50);
+        bas.set_voltage(3300);
+        assert_eq!(bas.level(), 100);
+        bas.set_level(150);
+        assert_eq!(bas.level(), 100);
+    }
+
+    #[test]
+    fn notifications() {
+        let mut bas = BatteryService::default();
+        bas.set_voltage(3000);
+        assert_eq!(bas.take_notification(), None);
+
+        bas.write_attr(Handle::from_raw(CCCD_HANDLE), &[0x01, 0x00])
+            .unwrap();
+        bas.set_voltage(2600);
+        let notification = bas.take_notification().unwrap();
+        assert_eq!(notification.handle(), Handle::from_raw(LEVEL_HANDLE));
+        assert_eq!(notification.value(), &[50]);
+        assert_eq!(bas.take_notification(), None);
+
+        // Unchanged levels are not notified
+        bas.set_voltage(2600);
+        assert_eq!(bas.take_notification(), None);
+
+        bas.set_voltage(2500);
+        bas.write_attr(Handle::from_raw(CCCD_HANDLE), &[0x00, 0x00])
+            .unwrap();
+        assert_eq!(bas.take_notification(), None);
+    }
+}
@@ -0,0 +1,86 @@
+/// Time in milliseconds an input must be steady before a change is accepted.
+pub const DEBOUNCE_MS: u32 = 20;
+
+/// Debounces a button by waiting for its input to be steady for `DEBOUNCE_MS`.
+///
+/// Every edge of the raw input restarts the wait, so a bouncing contact only results in one
+/// change of the debounced state once it has settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Debouncer {
+    stable: bool,
+    raw: bool,
+    changed_at: u32,
+}
+
+impl Debouncer {
+    pub const fn new() -> Self {
+        Self {
+            stable: false,
+            raw: false,
+            changed_at: 0,
+        }
+    }
+
+    /// The debounced state, which is true while the button is pressed.
+    pub fn is_pressed(&self) -> bool {
+        self.stable
+    }
+
+    /// Record an edge of the raw input at `now` milliseconds, with the input level read after
+    /// the edge.
+    pub fn edge(&mut self, now: u32, pressed: bool) {
+        self.raw = pressed;
+        self.changed_at = now;
+    }
+
+    /// Returns the new debounced state if the input has settled in another state by `now`.
+    pub fn update(&mut self, now: u32) -> Option<bool> {
+        if self.raw != self.stable && now.wrapping_sub(self.changed_at) >= DEBOUNCE_MS {
+            self.stable = self.raw;
+            Some(self.stable)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Debouncer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bouncing_press() {
+        let mut debouncer = Debouncer::new();
+        debouncer.edge(100, true);
+        debouncer.edge(102, false);
+        debouncer.edge(103, true);
+        assert_eq!(debouncer.update(110), None);
+        assert_eq!(debouncer.update(122), None);
+        assert_eq!(debouncer.update(123), Some(true));
+        assert!(debouncer.is_pressed());
+        assert_eq!(debouncer.update(200), None);
+    }
+
+    #[test]
+    fn glitch() {
+        let mut debouncer = Debouncer::new();
+        debouncer.edge(100, true);
+        debouncer.edge(105, false);
+        assert_eq!(debouncer.update(200), None);
+        assert!(!debouncer.is_pressed());
+    }
+
+    #[test]
+    fn wrapping_time() {
+        let mut debouncer = Debouncer::new();
+        debouncer.edge(u32::MAX - 5, true);
+        assert_eq!(debouncer.update(10), None);
+        assert_eq!(debouncer.update(14), Some(true));
+    }
+}
@@ -0,0 +1,314 @@
+use crate::debounce::Debouncer;
+use heapless::spsc::Queue;
+use heapless::{consts, ArrayLength};
+
+/// Time in milliseconds a button must be held to be long pressed.
+pub const LONG_PRESS_MS: u32 = 1000;
+
+/// Maximum time in milliseconds between two clicks of a double click.
+pub const DOUBLE_CLICK_MS: u32 = 400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    A,
+    B,
+}
+
+impl Button {
+    fn other(self) -> Button {
+        match self {
+            Button::A => Button::B,
+            Button::B => Button::A,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A button was pressed.
+    Pressed(Button),
+    /// A button was released.
+    Released(Button),
+    /// A button was pressed and released, without being long pressed or part of a chord.
+    Click(Button),
+    /// A button was clicked twice within `DOUBLE_CLICK_MS`. This follows the `Click` of the
+    /// second click.
+    DoubleClick(Button),
+    /// A button has been held for `LONG_PRESS_MS`.
+    LongPress(Button),
+    /// Both buttons are pressed at the same time. Releasing them does not result in clicks.
+    Chord,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct State {
+    debouncer: Debouncer,
+    pressed_at: u32,
+    long_pressed: bool,
+    chord: bool,
+    last_click: Option<u32>,
+}
+
+impl State {
+    const fn new() -> Self {
+        Self {
+            debouncer: Debouncer::new(),
+            pressed_at: 0,
+            long_pressed: false,
+            chord: false,
+            last_click: None,
+        }
+    }
+}
+
+/// Turns the edges of the A and B inputs into debounced button events.
+///
+/// The detector is driven by the caller with timestamps in milliseconds, which may wrap: `edge` on
+/// each edge of an input, and `update` periodically (every 10 ms or so) to complete debouncing
+/// and detect long presses. Events are queued until taken with `next_event`. When the queue of
+/// `N` events is full, new events are dropped.
+///
+/// ```
+/// use drogue_microbit_buttons::{Button, ButtonEvents, Event};
+///
+/// let mut buttons: ButtonEvents = ButtonEvents::new();
+/// buttons.edge(Button::A, 0, true);
+/// buttons.update(20);
+/// buttons.edge(Button::A, 100, false);
+/// buttons.update(120);
+///
+/// assert_eq!(buttons.next_event(), Some(Event::Pressed(Button::A)));
+/// assert_eq!(buttons.next_event(), Some(Event::Released(Button::A)));
+/// assert_eq!(buttons.next_event(), Some(Event::Click(Button::A)));
+/// assert_eq!(buttons.next_event(), None);
+/// ```
+pub struct ButtonEvents<N: ArrayLength<Event> = consts::U8> {
+    states: [State; 2],
+    events: Queue<Event, N>,
+}
+
+impl<N: ArrayLength<Event>> ButtonEvents<N> {
+    pub fn new() -> Self {
+        Self {
+            states: [State::new(); 2],
+            events: Queue::new(),
+        }
+    }
+
+    /// The debounced state of a button.
+    pub fn is_pressed(&self, button: Button) -> bool {
+        self.states[button as usize].debouncer.is_pressed()
+    }
+
+    /// Record an edge of the input of a button, with the level read after the edge.
+    pub fn edge(&mut self, button: Button, now: u32, pressed: bool) {
+        self.states[button as usize].debouncer.edge(now, pressed);
+    }
+
+    /// Complete debouncing of both buttons and detect long presses at `now`.
+    pub fn update(&mut self, now: u32) {
+        for button in [Button::A, Button::B].iter().copied() {
+            match self.states[button as usize].debouncer.update(now) {
+                Some(true) => self.pressed(button, now),
+                Some(false) => self.released(button, now),
+                None => {}
+            }
+
+            let state = &mut self.states[button as usize];
+            if state.debouncer.is_pressed()
+                && !state.long_pressed
+                && !state.chord
+                && now.wrapping_sub(state.pressed_at) >= LONG_PRESS_MS
+            {
+                state.long_pressed = true;
+                self.push(Event::LongPress(button));
+            }
+        }
+    }
+
+    /// Take the oldest queued event.
+    pub fn next_event(&mut self) -> Option<Event> {
+        self.events.dequeue()
+    }
+
+    fn pressed(&mut self, button: Button, now: u32) {
+        let state = &mut self.states[button as usize];
+        state.pressed_at = now;
+        state.long_pressed = false;
+        self.push(Event::Pressed(button));
+
+        if self.is_pressed(button.other()) {
+            for state in self.states.iter_mut() {
+                state.chord = true;
+                state.last_click = None;
+            }
+            self.push(Event::Chord);
+        }
+    }
+
+    fn released(&mut self, button: Button, now: u32) {
+        self.push(Event::Released(button));
+
+        let state = &mut self.states[button as usize];
+        if state.chord || state.long_pressed {
+            state.chord = false;
+            return;
+        }
+        let double =
+            matches!(state.last_click, Some(at) if now.wrapping_sub(at) <= DOUBLE_CLICK_MS);
+        state.last_click = if double { None } else { Some(now) };
+        self.push(Event::Click(button));
+        if double {
+            self.push(Event::DoubleClick(button));
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        self.events.enqueue(event).ok();
+    }
+}
+
+impl<N: ArrayLength<Event>> Default for ButtonEvents<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Simulate a bouncing press or release of a button at `at`, settling 5 ms later.
+    fn bounce(buttons: &mut ButtonEvents<consts::U16>, button: Button, at: u32, pressed: bool) {
+        buttons.edge(button, at, pressed);
+        buttons.edge(button, at + 2, !pressed);
+        buttons.edge(button, at + 5, pressed);
+    }
+
+    /// Run `update` every 10 ms from `from` until `to`.
+    fn run(buttons: &mut ButtonEvents<consts::U16>, from: u32, to: u32) {
+        let mut now = from;
+        while now <= to {
+            buttons.update(now);
+            now += 10;
+        }
+    }
+
+    fn events(buttons: &mut ButtonEvents<consts::U16>) -> heapless::Vec<Event, consts::U16> {
+        let mut events = heapless::Vec::new();
+        while let Some(event) = buttons.next_event() {
+            events.push(event).unwrap();
+        }
+        events
+    }
+
+    #[test]
+    fn click() {
+        let mut buttons = ButtonEvents::new();
+        bounce(&mut buttons, Button::B, 0, true);
+        run(&mut buttons, 0, 100);
+        assert!(buttons.is_pressed(Button::B));
+        bounce(&mut buttons, Button::B, 100, false);
+        run(&mut buttons, 100, 200);
+        assert_eq!(
+            &events(&mut buttons)[..],
+            &[
+                Event::Pressed(Button::B),
+                Event::Released(Button::B),
+                Event::Click(Button::B)
+            ]
+        );
+    }
+
+    #[test]
+    fn double_click() {
+        let mut buttons = ButtonEvents::new();
+        let mut clicks: heapless::Vec<Event, consts::U16> = heapless::Vec::new();
+        for at in [0, 200, 1000, 1600, 1800].iter().copied() {
+            bounce(&mut buttons, Button::A, at, true);
+            run(&mut buttons, at, at + 90);
+            bounce(&mut buttons, Button::A, at + 100, false);
+            run(&mut buttons, at + 100, at + 190);
+            for event in events(&mut buttons).iter().copied() {
+                if let Event::Click(_) | Event::DoubleClick(_) = event {
+                    clicks.push(event).unwrap();
+                }
+            }
+        }
+        assert_eq!(
+            &clicks[..],
+            &[
+                Event::Click(Button::A),
+                Event::Click(Button::A),
+                Event::DoubleClick(Button::A),
+                // A third click starts over
+                Event::Click(Button::A),
+                // Too long after the last click
+                Event::Click(Button::A),
+                Event::Click(Button::A),
+                Event::DoubleClick(Button::A),
+            ]
+        );
+    }
+
+    #[test]
+    fn long_press() {
+        let mut buttons = ButtonEvents::new();
+        bounce(&mut buttons, Button::A, 0, true);
+        run(&mut buttons, 0, 990);
+        assert_eq!(&events(&mut buttons)[..], &[Event::Pressed(Button::A)]);
+        run(&mut buttons, 1000, 2000);
+        assert_eq!(&events(&mut buttons)[..], &[Event::LongPress(Button::A)]);
+        bounce(&mut buttons, Button::A, 2000, false);
+        run(&mut buttons, 2000, 2100);
+        assert_eq!(&events(&mut buttons)[..], &[Event::Released(Button::A)]);
+    }
+
+    #[test]
+    fn chord() {
+        let mut buttons = ButtonEvents::new();
+        bounce(&mut buttons, Button::A, 0, true);
+        run(&mut buttons, 0, 40);
+        bounce(&mut buttons, Button::B, 50, true);
+        run(&mut buttons, 50, 1500);
+        bounce(&mut buttons, Button::A, 1500, false);
+        bounce(&mut buttons, Button::B, 1510, false);
+        run(&mut buttons, 1500, 1600);
+        assert_eq!(
+            &events(&mut buttons)[..],
+            &[
+                Event::Pressed(Button::A),
+                Event::Pressed(Button::B),
+                Event::Chord,
+                Event::Released(Button::A),
+                Event::Released(Button::B),
+            ]
+        );
+
+        // The buttons work independently again after the chord
+        bounce(&mut buttons, Button::A, 2000, true);
+        run(&mut buttons, 2000, 2040);
+        bounce(&mut buttons, Button::A, 2050, false);
+        run(&mut buttons, 2050, 2100);
+        assert_eq!(
+            &events(&mut buttons)[..],
+            &[
+                Event::Pressed(Button::A),
+                Event::Released(Button::A),
+                Event::Click(Button::A),
+            ]
+        );
+    }
+
+    #[test]
+    fn full_queue() {
+        let mut buttons: ButtonEvents<consts::U2> = ButtonEvents::new();
+        buttons.edge(Button::A, 0, true);
+        buttons.update(20);
+        buttons.edge(Button::A, 50, false);
+        buttons.update(70);
+        assert_eq!(buttons.next_event(), Some(Event::Pressed(Button::A)));
+        assert_eq!(buttons.next_event(), Some(Event::Released(Button::A)));
+        assert_eq!(buttons.next_event(), None);
+    }
+}
@@ -0,0 +1,16 @@
+//! Driver for buttons A and B of the micro:bit, with debouncing and detection of clicks, double
+//! clicks, long presses and chords of both buttons.
+//!
+//! The event detection in `ButtonEvents` is independent of the hardware. With the `v1` feature,
+//! `Buttons` feeds it from GPIOTE interrupts on the micro:bit v1.
+#![no_std]
+
+mod debounce;
+mod events;
+#[cfg(feature = "v1")]
+mod v1;
+
+pub use debounce::{Debouncer, DEBOUNCE_MS};
+pub use events::{Button, ButtonEvents, Event, DOUBLE_CLICK_MS, LONG_PRESS_MS};
+#[cfg(feature = "v1")]
+pub use v1::Buttons;
@@ -0,0 +1,73 @@
+use embedded_hal::digital::v2::InputPin;
+use hal::gpio::p0::{P0_17, P0_26};
+use hal::gpio::{Floating, Input, Pin};
+use hal::pac::GPIOTE;
+use heapless::{consts, ArrayLength};
+use nrf51_hal as hal;
+
+use crate::{Button, ButtonEvents, Event};
+
+/// GPIOTE channels used for buttons A and B.
+const CHANNELS: [usize; 2] = [0, 1];
+
+/// Buttons A (P0.17) and B (P0.26) of the micro:bit v1.
+///
+/// Edges are detected with GPIOTE channels 0 and 1, so `on_interrupt` must be called from the
+/// GPIOTE interrupt handler, and `update` periodically to complete debouncing and detect long
+/// presses. Both take the current time in milliseconds.
+pub struct Buttons<N: ArrayLength<Event> = consts::U8> {
+    gpiote: GPIOTE,
+    pins: [Pin<Input<Floating>>; 2],
+    events: ButtonEvents<N>,
+}
+
+impl<N: ArrayLength<Event>> Buttons<N> {
+    /// The buttons have external pull-up resistors, so the pins are used as floating inputs.
+    pub fn new(gpiote: GPIOTE, a: P0_17<Input<Floating>>, b: P0_26<Input<Floating>>) -> Self {
+        for (channel, pin) in CHANNELS.iter().zip([17, 26].iter()) {
+            gpiote.config[*channel]
+                .write(|w| unsafe { w.mode().event().psel().bits(*pin).polarity().toggle() });
+            gpiote.events_in[*channel].reset();
+        }
+        gpiote.intenset.write(|w| w.in0().set().in1().set());
+        Self {
+            gpiote,
+            pins: [a.degrade(), b.degrade()],
+            events: ButtonEvents::new(),
+        }
+    }
+
+    /// Handle the GPIOTE interrupt, recording the edges of the buttons.
+    pub fn on_interrupt(&mut self, now: u32) {
+        for button in [Button::A, Button::B].iter().copied() {
+            let channel = CHANNELS[button as usize];
+            if self.gpiote.events_in[channel].read().bits() != 0 {
+                self.gpiote.events_in[channel].reset();
+                // The buttons are active low
+                let pressed = self.pins[button as usize].is_low().unwrap();
+                self.events.edge(button, now, pressed);
+            }
+        }
+    }
+
+    /// Complete debouncing and detect long presses.
+    pub fn update(&mut self, now: u32) {
+        self.events.update(now);
+    }
+
+    pub fn is_pressed(&self, button: Button) -> bool {
+        self.events.is_pressed(button)
+    }
+
+    /// Take the oldest queued event.
+    pub fn next_event(&mut self) -> Option<Event> {
+        self.events.next_event()
+    }
+
+    pub fn free(self) -> (GPIOTE, [Pin<Input<Floating>>; 2]) {
+        self.gpiote
+            .intenclr
+            .write(|w| w.in0().clear().in1().clear());
+        (self.gpiote, self.pins)
+    }
+}
@@ -0,0 +1,146 @@
+#![no_std]
+
+use drogue_microbit_gatt::{impl_provider, BuildError, GattTable, Properties};
+use heapless::consts;
+use rubble::uuid::Uuid16;
+
+pub const DIS_UUID: Uuid16 = Uuid16(0x180A);
+const MANUFACTURER_NAME_UUID: Uuid16 = Uuid16(0x2A29);
+const MODEL_NUMBER_UUID: Uuid16 = Uuid16(0x2A24);
+const SERIAL_NUMBER_UUID: Uuid16 = Uuid16(0x2A25);
+const FIRMWARE_REVISION_UUID: Uuid16 = Uuid16(0x2A26);
+const HARDWARE_REVISION_UUID: Uuid16 = Uuid16(0x2A27);
+
+/// The strings reported by the Device Information Service. Each must be at most `MAX_VALUE_LEN`
+/// bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceInformation<'a> {
+    pub manufacturer_name: &'a str,
+    pub model_number: &'a str,
+    pub serial_number: &'a str,
+    pub firmware_revision: &'a str,
+    pub hardware_revision: &'a str,
+}
+
+/// A serial number formatted from the 64-bit device identifier in FICR, as 16 hexadecimal digits.
+///
+/// ```ignore
+/// let ficr = &ctx.device.FICR;
+/// let serial = SerialNumber::from_device_id([
+///     ficr.deviceid[0].read().bits(),
+///     ficr.deviceid[1].read().bits(),
+/// ]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialNumber([u8; 16]);
+
+impl SerialNumber {
+    /// Create from the `DEVICEID` registers, where `device_id[1]` holds the most significant word.
+    pub fn from_device_id(device_id: [u32; 2]) -> Self {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+        let id = (u64::from(device_id[1]) << 32) | u64::from(device_id[0]);
+        let mut digits = [0; 16];
+        for (i, digit) in digits.iter_mut().enumerate() {
+            *digit = HEX[((id >> (60 - 4 * i)) & 0xF) as usize];
+        }
+        SerialNumber(digits)
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ASCII digits are stored
+        core::str::from_utf8(&self.0).unwrap()
+    }
+}
+
+/// An `AttributeProvider` that will enumerate as a Device Information Service.
+pub struct DeviceInformationService {
+    table: GattTable<consts::U11>,
+}
+
+impl DeviceInformationService {
+    /// Create the service. Fails with `ValueTooLong` if a string does not fit in an attribute.
+    pub fn new(info: &DeviceInformation) -> Result<Self, BuildError> {
+        let mut table = GattTable::builder();
+        table.service(DIS_UUID)?;
+        for (uuid, value) in [
+            (MANUFACTURER_NAME_UUID, info.manufacturer_name),
+            (MODEL_NUMBER_UUID, info.model_number),
+            (SERIAL_NUMBER_UUID, info.serial_number),
+            (FIRMWARE_REVISION_UUID, info.firmware_revision),
+            (HARDWARE_REVISION_UUID, info.hardware_revision),
+        ]
+        .iter()
+        {
+            table.characteristic(*uuid, Properties::READ, value.as_bytes())?;
+        }
+        Ok(Self {
+            table: table.build(),
+        })
+    }
+}
+
+impl_provider!(DeviceInformationService);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drogue_microbit_gatt::GattProvider;
+    use rubble::att::{AttributeProvider, Handle, HandleRange};
+    use rubble::Error;
+
+    const INFO: DeviceInformation = DeviceInformation {
+        manufacturer_name: "Drogue IoT",
+        model_number: "BBC micro:bit",
+        serial_number: "0123456789ABCDEF",
+        firmware_revision: "0.1.0",
+        hardware_revision: "1.5",
+    };
+
+    fn read(dis: &mut DeviceInformationService, handle: u16) -> heapless::Vec<u8, consts::U20> {
+        let mut value = heapless::Vec::new();
+        let handle = Handle::from_raw(handle);
+        dis.for_attrs_in_range(HandleRange::new(handle, handle), |_, attr| {
+            value.extend_from_slice(attr.value.as_slice()).unwrap();
+            Ok(())
+        })
+        .unwrap();
+        value
+    }
+
+    #[test]
+    fn serial_number() {
+        let serial = SerialNumber::from_device_id([0x89AB_CDEF, 0x0123_4567]);
+        assert_eq!(serial.as_str(), "0123456789ABCDEF");
+        let serial = SerialNumber::from_device_id([0x0000_000A, 0x0000_0000]);
+        assert_eq!(serial.as_str(), "000000000000000A");
+    }
+
+    #[test]
+    fn characteristics() {
+        let mut dis = DeviceInformationService::new(&INFO).unwrap();
+        assert_eq!(&read(&mut dis, 1)[..], &[0x0A, 0x18]);
+        assert_eq!(&read(&mut dis, 2)[..], &[0x02, 0x03, 0x00, 0x29, 0x2A]);
+        assert_eq!(&read(&mut dis, 3)[..], b"Drogue IoT");
+        assert_eq!(&read(&mut dis, 5)[..], b"BBC micro:bit");
+        assert_eq!(&read(&mut dis, 7)[..], b"0123456789ABCDEF");
+        assert_eq!(&read(&mut dis, 9)[..], b"0.1.0");
+        assert_eq!(&read(&mut dis, 11)[..], b"1.5");
+        assert_eq!(dis.last_handle(), 11);
+        assert_eq!(
+            dis.write_attr(Handle::from_raw(3), b"Other"),
+            Err(Error::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn too_long() {
+        let info = DeviceInformation {
+            model_number: "BBC micro:bit with a long name",
+            ..INFO
+        };
+        assert_eq!(
+            DeviceInformationService::new(&info).err(),
+            Some(BuildError::ValueTooLong)
+        );
+    }
+}
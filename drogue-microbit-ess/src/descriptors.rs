@@ -0,0 +1,309 @@
+//! Typed values of the Environmental Sensing descriptors.
+
+use crate::sensor::Format;
+use rubble::Error;
+
+/// How the measurement is sampled, as reported in the ES Measurement descriptor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamplingFunction {
+    Unspecified = 0x00,
+    Instantaneous = 0x01,
+    ArithmeticMean = 0x02,
+    Rms = 0x03,
+    Maximum = 0x04,
+    Minimum = 0x05,
+    Accumulated = 0x06,
+    Count = 0x07,
+}
+
+/// What the measurement applies to, as reported in the ES Measurement descriptor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Application(pub u8);
+
+impl Application {
+    pub const UNSPECIFIED: Application = Application(0x00);
+    pub const AIR: Application = Application(0x01);
+    pub const WATER: Application = Application(0x02);
+    pub const BAROMETRIC: Application = Application(0x03);
+    pub const SOIL: Application = Application(0x04);
+    pub const INFRARED: Application = Application(0x05);
+    pub const OUTDOOR: Application = Application(0x13);
+    pub const INDOOR: Application = Application(0x14);
+    pub const INTERNAL: Application = Application(0x1F);
+    pub const EXTERNAL: Application = Application(0x20);
+}
+
+/// Contents of the ES Measurement descriptor (0x290C).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    pub sampling_function: SamplingFunction,
+    /// Period over which the measurement is taken, in seconds. 0 if not in use.
+    pub measurement_period: u32,
+    /// Interval between internal updates of the value, in seconds. 0 if not in use.
+    pub update_interval: u32,
+    pub application: Application,
+    /// Uncertainty of the measurement, in units of 0.5%. 0xFF if not known.
+    pub uncertainty: u8,
+}
+
+/// Largest value of the uint24 fields of the descriptors.
+const U24_MAX: u32 = 0x00FF_FFFF;
+
+impl Measurement {
+    pub(crate) fn encode(&self) -> [u8; 11] {
+        let period = self.measurement_period.min(U24_MAX).to_le_bytes();
+        let interval = self.update_interval.min(U24_MAX).to_le_bytes();
+        [
+            0x00,
+            0x00, // Flags (reserved)
+            self.sampling_function as u8,
+            period[0],
+            period[1],
+            period[2],
+            interval[0],
+            interval[1],
+            interval[2],
+            self.application.0,
+            self.uncertainty,
+        ]
+    }
+}
+
+impl Default for Measurement {
+    fn default() -> Self {
+        Measurement {
+            sampling_function: SamplingFunction::Instantaneous,
+            measurement_period: 0,
+            update_interval: 0,
+            application: Application::AIR,
+            uncertainty: 0xFF,
+        }
+    }
+}
+
+/// Condition for sending notifications, as set in the ES Trigger Setting descriptor (0x290D).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerCondition {
+    Inactive,
+    /// Notify at a fixed interval, in seconds.
+    FixedInterval(u32),
+    /// Notify when the value changes, but not more often than the interval, in seconds.
+    MinimumInterval(u32),
+    /// Notify when the value changes.
+    OnChange,
+    // The operand of the value conditions is in the unit of the characteristic, i.e. 0.01 degrees
+    // Celsius for the temperature.
+    LessThan(i32),
+    LessThanOrEqual(i32),
+    GreaterThan(i32),
+    GreaterThanOrEqual(i32),
+    EqualTo(i32),
+    NotEqualTo(i32),
+}
+
+/// Largest encoded size of a trigger setting.
+pub(crate) const MAX_TRIGGER_LEN: usize = 5;
+
+impl TriggerCondition {
+    /// Encode into `buf` for a characteristic of the given format, returning the number of bytes
+    /// used.
+    pub(crate) fn encode(&self, format: Format, buf: &mut [u8; MAX_TRIGGER_LEN]) -> usize {
+        match *self {
+            TriggerCondition::Inactive => encode_condition(buf, 0x00),
+            TriggerCondition::FixedInterval(s) => encode_interval(buf, 0x01, s),
+            TriggerCondition::MinimumInterval(s) => encode_interval(buf, 0x02, s),
+            TriggerCondition::OnChange => encode_condition(buf, 0x03),
+            TriggerCondition::LessThan(t) => encode_value(buf, 0x04, format, t),
+            TriggerCondition::LessThanOrEqual(t) => encode_value(buf, 0x05, format, t),
+            TriggerCondition::GreaterThan(t) => encode_value(buf, 0x06, format, t),
+            TriggerCondition::GreaterThanOrEqual(t) => encode_value(buf, 0x07, format, t),
+            TriggerCondition::EqualTo(t) => encode_value(buf, 0x08, format, t),
+            TriggerCondition::NotEqualTo(t) => encode_value(buf, 0x09, format, t),
+        }
+    }
+
+    /// Decode a written trigger setting for a characteristic of the given format.
+    pub(crate) fn decode(format: Format, data: &[u8]) -> Result<Self, Error> {
+        let (&condition, operand) = data.split_first().ok_or(Error::InvalidLength)?;
+        let interval = || match operand {
+            [a, b, c] => Ok(u32::from_le_bytes([*a, *b, *c, 0])),
+            _ => Err(Error::InvalidLength),
+        };
+        let value = || format.decode(operand).ok_or(Error::InvalidLength);
+        let none = || {
+            if operand.is_empty() {
+                Ok(())
+            } else {
+                Err(Error::InvalidLength)
+            }
+        };
+        Ok(match condition {
+            0x00 => none().map(|_| TriggerCondition::Inactive)?,
+            0x01 => TriggerCondition::FixedInterval(interval()?),
+            0x02 => TriggerCondition::MinimumInterval(interval()?),
+            0x03 => none().map(|_| TriggerCondition::OnChange)?,
+            0x04 => TriggerCondition::LessThan(value()?),
+            0x05 => TriggerCondition::LessThanOrEqual(value()?),
+            0x06 => TriggerCondition::GreaterThan(value()?),
+            0x07 => TriggerCondition::GreaterThanOrEqual(value()?),
+            0x08 => TriggerCondition::EqualTo(value()?),
+            0x09 => TriggerCondition::NotEqualTo(value()?),
+            _ => return Err(Error::InvalidValue),
+        })
+    }
+
+    /// Result of comparing `value` against the operand, if this is a value condition.
+    fn compare(&self, value: i32) -> Option<bool> {
+        match *self {
+            TriggerCondition::LessThan(t) => Some(value < t),
+            TriggerCondition::LessThanOrEqual(t) => Some(value <= t),
+            TriggerCondition::GreaterThan(t) => Some(value > t),
+            TriggerCondition::GreaterThanOrEqual(t) => Some(value >= t),
+            TriggerCondition::EqualTo(t) => Some(value == t),
+            TriggerCondition::NotEqualTo(t) => Some(value != t),
+            _ => None,
+        }
+    }
+
+    /// Returns true if a notification should be sent.
+    ///
+    /// `changed` is true if this is evaluated because the value was updated, and `elapsed` is the
+    /// number of seconds since the last notification, if any.
+    pub(crate) fn fires(&self, value: i32, changed: bool, elapsed: Option<u32>) -> bool {
+        let due = |interval: u32| !matches!(elapsed, Some(e) if e < interval);
+        match *self {
+            TriggerCondition::Inactive => false,
+            TriggerCondition::FixedInterval(s) => due(s),
+            TriggerCondition::MinimumInterval(s) => changed && due(s),
+            TriggerCondition::OnChange => changed,
+            _ => changed && self.compare(value).unwrap_or(false),
+        }
+    }
+}
+
+fn encode_condition(buf: &mut [u8; MAX_TRIGGER_LEN], condition: u8) -> usize {
+    buf[0] = condition;
+    1
+}
+
+fn encode_interval(buf: &mut [u8; MAX_TRIGGER_LEN], condition: u8, seconds: u32) -> usize {
+    buf[0] = condition;
+    buf[1..4].copy_from_slice(&seconds.min(U24_MAX).to_le_bytes()[..3]);
+    4
+}
+
+fn encode_value(
+    buf: &mut [u8; MAX_TRIGGER_LEN],
+    condition: u8,
+    format: Format,
+    value: i32,
+) -> usize {
+    buf[0] = condition;
+    1 + format.encode(value, &mut buf[1..])
+}
+
+/// How multiple trigger conditions are combined, as set in the ES Configuration descriptor
+/// (0x290B).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerLogic {
+    /// Notify when all active conditions are met.
+    And = 0x00,
+    /// Notify when any condition is met.
+    Or = 0x01,
+}
+
+impl TriggerLogic {
+    pub(crate) fn decode(data: &[u8]) -> Result<Self, Error> {
+        match data {
+            [0x00] => Ok(TriggerLogic::And),
+            [0x01] => Ok(TriggerLogic::Or),
+            [_] => Err(Error::InvalidValue),
+            _ => Err(Error::InvalidLength),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_measurement() {
+        let measurement = Measurement {
+            sampling_function: SamplingFunction::ArithmeticMean,
+            measurement_period: 60,
+            update_interval: 0x0123_4567,
+            application: Application::AIR,
+            uncertainty: 4,
+        };
+        assert_eq!(
+            measurement.encode(),
+            [0x00, 0x00, 0x02, 60, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0x01, 4]
+        );
+    }
+
+    #[test]
+    fn encode_triggers() {
+        let mut buf = [0; MAX_TRIGGER_LEN];
+        assert_eq!(TriggerCondition::Inactive.encode(Format::S16, &mut buf), 1);
+        assert_eq!(buf[0], 0x00);
+
+        let interval = TriggerCondition::FixedInterval(300);
+        assert_eq!(interval.encode(Format::S16, &mut buf), 4);
+        assert_eq!(buf[..4], [0x01, 0x2C, 0x01, 0x00]);
+
+        let limit = TriggerCondition::LessThan(-500);
+        assert_eq!(limit.encode(Format::S16, &mut buf), 3);
+        assert_eq!(buf[..3], [0x04, 0x0C, 0xFE]);
+
+        let limit = TriggerCondition::GreaterThan(1_013_250);
+        assert_eq!(limit.encode(Format::U32, &mut buf), 5);
+        assert_eq!(buf, [0x06, 0x02, 0x76, 0x0F, 0x00]);
+    }
+
+    #[test]
+    fn decode_triggers() {
+        let decode = |data: &[u8]| TriggerCondition::decode(Format::S16, data);
+        assert_eq!(decode(&[0x00]), Ok(TriggerCondition::Inactive));
+        assert_eq!(decode(&[0x03]), Ok(TriggerCondition::OnChange));
+        assert_eq!(
+            decode(&[0x01, 0x2C, 0x01, 0x00]),
+            Ok(TriggerCondition::FixedInterval(300))
+        );
+        assert_eq!(
+            decode(&[0x04, 0x0C, 0xFE]),
+            Ok(TriggerCondition::LessThan(-500))
+        );
+        assert_eq!(decode(&[]), Err(Error::InvalidLength));
+        assert_eq!(decode(&[0x03, 0x00]), Err(Error::InvalidLength));
+        assert_eq!(decode(&[0x02, 0x01]), Err(Error::InvalidLength));
+        assert_eq!(decode(&[0x06, 0x01, 0x00, 0x00]), Err(Error::InvalidLength));
+        assert_eq!(decode(&[0x0A]), Err(Error::InvalidValue));
+
+        assert_eq!(TriggerLogic::decode(&[0x00]), Ok(TriggerLogic::And));
+        assert_eq!(TriggerLogic::decode(&[0x02]), Err(Error::InvalidValue));
+        assert_eq!(TriggerLogic::decode(&[]), Err(Error::InvalidLength));
+    }
+
+    #[test]
+    fn trigger_conditions() {
+        let t = 2000;
+        let limit = TriggerCondition::GreaterThan(2500);
+        assert!(!limit.fires(t, true, None));
+        assert!(limit.fires(2600, true, None));
+        assert!(!limit.fires(2600, false, None));
+
+        let fixed = TriggerCondition::FixedInterval(10);
+        assert!(fixed.fires(t, false, None));
+        assert!(!fixed.fires(t, false, Some(9)));
+        assert!(fixed.fires(t, false, Some(10)));
+
+        let min = TriggerCondition::MinimumInterval(10);
+        assert!(!min.fires(t, false, Some(20)));
+        assert!(!min.fires(t, true, Some(5)));
+        assert!(min.fires(t, true, Some(20)));
+
+        assert!(TriggerCondition::OnChange.fires(t, true, Some(0)));
+        assert!(!TriggerCondition::Inactive.fires(t, true, None));
+    }
+}
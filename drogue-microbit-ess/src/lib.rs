@@ -1,133 +1,968 @@
 #![no_std]
 
 use core::cmp;
-use rubble::att::{AttUuid, AttrValue, Attribute, AttributeProvider, Handle, HandleRange};
+use drogue_microbit_gatt::{
+    impl_provider, AttributeId, BuildError, Entry, GattTable, Properties,
+    CHARACTERISTIC_USER_DESCRIPTION_UUID,
+};
+use fixed::types::I30F2;
+use heapless::{consts, ArrayLength, Vec};
+use rubble::att::{AttributeAccessPermissions, AttributeProvider, Handle};
 use rubble::uuid::Uuid16;
 use rubble::Error;
 
-#[derive(Debug)]
-pub enum Value {
-    ServiceDef([u8; 2]),
-    CharDef([u8; 5]),
-    CharValue([u8; 4]),
+mod descriptors;
+mod sensor;
+
+use descriptors::MAX_TRIGGER_LEN;
+pub use descriptors::{Application, Measurement, SamplingFunction, TriggerCondition, TriggerLogic};
+pub use drogue_microbit_gatt::{Notification, MAX_VALUE_LEN};
+pub use sensor::{Format, Sensor};
+
+/// A temperature in units of 0.01 degrees Celsius, which is the resolution of the ESS Temperature
+/// characteristic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Temperature(i16);
+
+impl Temperature {
+    /// Value reported when the temperature is not known.
+    pub const UNKNOWN: Temperature = Temperature(i16::MIN);
+
+    /// Create a temperature from hundredths of a degree Celsius.
+    pub fn from_centi_celsius(value: i16) -> Self {
+        Temperature(value)
+    }
+
+    /// The temperature in hundredths of a degree Celsius.
+    pub fn centi_celsius(&self) -> i16 {
+        self.0
+    }
+
+    /// Encode as the little-endian sint16 used by the Temperature characteristic.
+    pub fn to_le_bytes(&self) -> [u8; 2] {
+        self.0.to_le_bytes()
+    }
+}
+
+impl From<f32> for Temperature {
+    /// Convert from degrees Celsius, rounding to the nearest hundredth. Temperatures outside the
+    /// range of the characteristic are saturated, and NaN is reported as unknown.
+    fn from(celsius: f32) -> Self {
+        if celsius.is_nan() {
+            return Temperature::UNKNOWN;
+        }
+        let centi = celsius * 100.0;
+        let centi = if centi >= 0.0 {
+            centi + 0.5
+        } else {
+            centi - 0.5
+        };
+        // i16::MIN is reserved for unknown values
+        let centi = cmp::min(cmp::max(centi as i32, -(i16::MAX as i32)), i16::MAX as i32);
+        Temperature(centi as i16)
+    }
+}
+
+impl From<I30F2> for Temperature {
+    /// Convert from the quarter degrees Celsius measured by the on-die temperature sensor, which
+    /// is exact within the range of the characteristic. Temperatures outside it are saturated.
+    fn from(celsius: I30F2) -> Self {
+        let centi = celsius.to_bits().saturating_mul(25);
+        let centi = cmp::min(cmp::max(centi, -(i16::MAX as i32)), i16::MAX as i32);
+        Temperature(centi as i16)
+    }
 }
 
 /// An `AttributeProvider` that will enumerate as a Environmental Sensing Service.
-pub struct EnvironmentSensingService {
-    attributes: [Attribute<Value>; 3],
+///
+/// The service holds up to `N` characteristics, added with a `Builder`. Each characteristic
+/// takes 7 attributes, and one more with a description, so the attribute table `A` must hold at
+/// least `1 + 7 * N` attributes.
+///
+/// ```
+/// use drogue_microbit_ess::{EnvironmentSensingService, Sensor};
+/// use heapless::consts;
+///
+/// let mut builder = EnvironmentSensingService::<consts::U2, consts::U15>::builder();
+/// let temperature = builder.add(Sensor::Temperature).unwrap();
+/// let humidity = builder.add(Sensor::Humidity).unwrap();
+/// let mut ess = builder.build();
+///
+/// ess.set_value(humidity, 4550); // 45.5%
+/// ```
+pub struct EnvironmentSensingService<
+    N: ArrayLength<Characteristic> = consts::U4,
+    A: ArrayLength<Entry> = consts::U29,
+> {
+    table: GattTable<A>,
+    characteristics: Vec<Characteristic, N>,
+    /// Current time in seconds, as given to `update_time`.
+    now: u32,
+    write_callback: Option<WriteCallback>,
+}
+
+/// Identifies a characteristic of an `EnvironmentSensingService`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensorId(usize);
+
+/// A write from the client to a descriptor of a characteristic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Write<'a> {
+    /// ES Trigger Setting with the given index.
+    Trigger(usize, TriggerCondition),
+    /// ES Configuration.
+    TriggerLogic(TriggerLogic),
+    /// Characteristic User Description.
+    Description(&'a str),
+}
+
+/// Called before a write from the client is applied. Returning an error rejects the write, and
+/// the error is reported to the client.
+pub type WriteCallback = fn(SensorId, &Write<'_>) -> Result<(), Error>;
+
+/// The writable descriptors of a characteristic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Descriptor {
+    Trigger(usize),
+    Configuration,
+    Description,
 }
 
-const PRIMARY_SERVICE_UUID: Uuid16 = Uuid16(0x2800);
-pub const ESS_UUID: Uuid16 = Uuid16(0x2803);
-const ESS_TEMPERATURE_MEASUREMENT: Uuid16 = Uuid16(0x2A1C);
+/// Number of ES Trigger Setting descriptors on each characteristic.
+pub const MAX_TRIGGERS: usize = 2;
 
-impl AttrValue for Value {
-    fn as_slice(&self) -> &[u8] {
-        match self {
-            Value::ServiceDef(v) => &v[..],
-            Value::CharDef(v) => &v[..],
-            Value::CharValue(v) => &v[..],
+pub const ESS_UUID: Uuid16 = Uuid16(0x181A);
+const ES_CONFIGURATION_UUID: Uuid16 = Uuid16(0x290B);
+const ES_MEASUREMENT_UUID: Uuid16 = Uuid16(0x290C);
+const ES_TRIGGER_SETTING_UUID: Uuid16 = Uuid16(0x290D);
+
+/// Declaration, value, CCCD, ES Measurement, ES Trigger Settings and ES Configuration, followed by
+/// an optional Characteristic User Description.
+const ATTRIBUTES_PER_CHARACTERISTIC: usize = 5 + MAX_TRIGGERS;
+
+/// The state of a characteristic of the service.
+pub struct Characteristic {
+    sensor: Sensor,
+    value_id: AttributeId,
+    measurement_id: AttributeId,
+    trigger_ids: [AttributeId; MAX_TRIGGERS],
+    configuration_id: AttributeId,
+    description_id: Option<AttributeId>,
+    value: i32,
+    notification_pending: bool,
+    measurement: Measurement,
+    triggers: [TriggerCondition; MAX_TRIGGERS],
+    trigger_logic: TriggerLogic,
+    /// Time of the last notification.
+    last_notification: Option<u32>,
+}
+
+impl Characteristic {
+    fn descriptor(&self, id: AttributeId) -> Option<Descriptor> {
+        if let Some(index) = self.trigger_ids.iter().position(|t| *t == id) {
+            Some(Descriptor::Trigger(index))
+        } else if id == self.configuration_id {
+            Some(Descriptor::Configuration)
+        } else if Some(id) == self.description_id {
+            Some(Descriptor::Description)
+        } else {
+            None
         }
     }
-}
 
-impl EnvironmentSensingService {
-    pub fn new() -> Self {
-        Self {
-            attributes: [
-                Attribute::new(
-                    AttUuid::Uuid16(PRIMARY_SERVICE_UUID),
-                    Handle::from_raw(0x0001),
-                    Value::ServiceDef([0x1A, 0x18]),
-                ), // "ES Service" = 0x181A
-                // Define temperature measurement
-                Attribute::new(
-                    AttUuid::Uuid16(ESS_UUID),
-                    Handle::from_raw(0x0002),
-                    Value::CharDef([
-                        0x02, // 1 byte properties: READ = 0x02, NOTIFY = 0x10
-                        0x03, 0x00, // 2 bytes handle = 0x0003
-                        0x1C, 0x2A, // 2 bytes UUID = 0x2A1C (Temperature measurement)
-                    ]),
-                ),
-                // Characteristic value (Temperature measurement)
-                Attribute::new(
-                    AttUuid::Uuid16(ESS_TEMPERATURE_MEASUREMENT),
-                    Handle::from_raw(0x0003),
-                    Value::CharValue([0; 4]),
-                ),
-                /*
-                // Define properties
-                Attribute {
-                    att_type: Uuid16(0x2803).into(), // "Characteristic"
-                    handle: Handle::from_raw(0x0005),
-                    value: HexSlice(&[
-                        0x02, // 1 byte properties: READ = 0x02
-                        0x06, 0x00, // 2 bytes handle = 0x0006
-                        0x0C, 0x29, // 2 bytes UUID = 0x2A1C (ES measurement)
-                    ]),
-                },
-                // Characteristic
-                Attribute {
-                    att_type: AttUuid::Uuid16(Uuid16(0x290C)),
-                    handle: Handle::from_raw(0x0006),
-                    value: HexSlice(&[
-                        0x00, 0x00, // Flags
-                        0x02, // Sampling function: Arithmetic mean
-                        0x00, 0x00, 0x00, // Measurement period
-                        0x00, 0x00, 0x00, // Update interval
-                        0x01, // Application: Air
-                        0x00, // Uncertainty
-                    ]),
-                },*/
-            ],
-        }
-    }
-
-    pub fn set_temperature(&mut self, value: u32) {
-        self.attributes[2].set_value(Value::CharValue([
-            (value >> 24) as u8,
-            (value >> 16) as u8,
-            (value >> 8) as u8,
-            value as u8,
-        ]));
+    fn evaluate_triggers(&mut self, notify: bool, changed: bool, now: u32) {
+        if !notify {
+            return;
+        }
+        let value = self.value;
+        let elapsed = self.last_notification.map(|last| now.wrapping_sub(last));
+        let mut active = self
+            .triggers
+            .iter()
+            .filter(|t| **t != TriggerCondition::Inactive);
+        let fire = |t: &TriggerCondition| t.fires(value, changed, elapsed);
+        let fired = match self.trigger_logic {
+            TriggerLogic::And => active.clone().next().is_some() && active.all(fire),
+            TriggerLogic::Or => active.any(fire),
+        };
+        if fired {
+            self.notification_pending = true;
+            self.last_notification = Some(now);
+        }
     }
 }
 
-impl AttributeProvider for EnvironmentSensingService {
-    fn for_attrs_in_range(
+fn encode_trigger(format: Format, condition: &TriggerCondition) -> ([u8; MAX_TRIGGER_LEN], usize) {
+    let mut buf = [0; MAX_TRIGGER_LEN];
+    let len = condition.encode(format, &mut buf);
+    (buf, len)
+}
+
+/// Builder for an `EnvironmentSensingService`, allocating handles to the characteristics in the
+/// order they are added.
+pub struct Builder<N: ArrayLength<Characteristic>, A: ArrayLength<Entry>> {
+    table: drogue_microbit_gatt::Builder<A>,
+    characteristics: Vec<Characteristic, N>,
+}
+
+impl<N: ArrayLength<Characteristic>, A: ArrayLength<Entry>> Builder<N, A> {
+    /// Add a characteristic to the service. Returns `None` if the service is full.
+    pub fn add(&mut self, sensor: Sensor) -> Option<SensorId> {
+        self.add_characteristic(sensor, None)
+    }
+
+    /// Add a characteristic with a Characteristic User Description, i.e. "Outdoor". The
+    /// description can be changed by the client. Returns `None` if the service is full or the
+    /// description is longer than `MAX_VALUE_LEN`.
+    pub fn add_with_description(&mut self, sensor: Sensor, description: &str) -> Option<SensorId> {
+        if description.len() > MAX_VALUE_LEN {
+            return None;
+        }
+        self.add_characteristic(sensor, Some(description))
+    }
+
+    pub fn build(self) -> EnvironmentSensingService<N, A> {
+        EnvironmentSensingService {
+            table: self.table.build(),
+            characteristics: self.characteristics,
+            now: 0,
+            write_callback: None,
+        }
+    }
+
+    fn add_characteristic(
         &mut self,
-        range: HandleRange,
-        mut f: impl FnMut(&Self, &Attribute<dyn AttrValue>) -> Result<(), Error>,
-    ) -> Result<(), Error> {
-        let count = self.attributes.len();
-        let start = usize::from(range.start().as_u16() - 1); // handles start at 1, not 0
-        let end = usize::from(range.end().as_u16() - 1);
+        sensor: Sensor,
+        description: Option<&str>,
+    ) -> Option<SensorId> {
+        let attributes = ATTRIBUTES_PER_CHARACTERISTIC + description.map_or(0, |_| 1);
+        if self.characteristics.len() == self.characteristics.capacity()
+            || self.table.remaining() < attributes
+        {
+            return None;
+        }
+        self.try_add_characteristic(sensor, description).ok()?;
+        Some(SensorId(self.characteristics.len() - 1))
+    }
 
-        // Update temperature before invoking callback
+    fn try_add_characteristic(
+        &mut self,
+        sensor: Sensor,
+        description: Option<&str>,
+    ) -> Result<(), BuildError> {
+        let format = sensor.format();
+        let value = sensor.initial_value();
+        let measurement = Measurement::default();
+        let triggers = [TriggerCondition::OnChange, TriggerCondition::Inactive];
+        let trigger_logic = TriggerLogic::Or;
 
-        let attrs = if start >= count {
-            &[]
-        } else {
-            let end = cmp::min(count - 1, end);
-            &self.attributes[start..=end]
+        let mut buf = [0; 4];
+        let len = format.encode(value, &mut buf);
+        let value_id = self.table.characteristic(
+            sensor.uuid(),
+            Properties::READ | Properties::NOTIFY,
+            &buf[..len],
+        )?;
+        let measurement_id = self.table.descriptor(
+            ES_MEASUREMENT_UUID,
+            &measurement.encode(),
+            AttributeAccessPermissions::Readable,
+        )?;
+        let mut trigger_ids = [value_id; MAX_TRIGGERS];
+        for (id, trigger) in trigger_ids.iter_mut().zip(triggers.iter()) {
+            let (buf, len) = encode_trigger(format, trigger);
+            *id = self.table.descriptor(
+                ES_TRIGGER_SETTING_UUID,
+                &buf[..len],
+                AttributeAccessPermissions::ReadableAndWriteable,
+            )?;
+        }
+        let configuration_id = self.table.descriptor(
+            ES_CONFIGURATION_UUID,
+            &[trigger_logic as u8],
+            AttributeAccessPermissions::ReadableAndWriteable,
+        )?;
+        let description_id = match description {
+            Some(description) => Some(self.table.descriptor(
+                CHARACTERISTIC_USER_DESCRIPTION_UUID,
+                description.as_bytes(),
+                AttributeAccessPermissions::ReadableAndWriteable,
+            )?),
+            None => None,
         };
 
-        for attr in attrs {
-            f(self, attr)?;
+        self.characteristics
+            .push(Characteristic {
+                sensor,
+                value_id,
+                measurement_id,
+                trigger_ids,
+                configuration_id,
+                description_id,
+                value,
+                notification_pending: false,
+                measurement,
+                triggers,
+                trigger_logic,
+                last_notification: None,
+            })
+            .map_err(|_| BuildError::TableFull)
+    }
+}
+
+impl<N: ArrayLength<Characteristic>, A: ArrayLength<Entry>> EnvironmentSensingService<N, A> {
+    /// Create a service with a single temperature characteristic.
+    pub fn new() -> Self {
+        let mut builder = Self::builder();
+        builder.add(Sensor::Temperature);
+        builder.build()
+    }
+
+    /// Create a builder for a service without any characteristics.
+    pub fn builder() -> Builder<N, A> {
+        let mut table = GattTable::builder();
+        // Only fails if the table has no room at all, in which case no characteristics can be
+        // added either
+        table.service(ESS_UUID).ok();
+        Builder {
+            table,
+            characteristics: Vec::new(),
+        }
+    }
+
+    /// Update the value of a characteristic, in the unit of the characteristic. Values outside
+    /// the range of the characteristic are saturated.
+    ///
+    /// If the client has enabled notifications and the trigger conditions are met, a
+    /// notification is queued which can be retrieved with `take_notification`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not a characteristic of this service.
+    pub fn set_value(&mut self, id: SensorId, value: i32) {
+        let characteristic = &mut self.characteristics[id.0];
+        let format = characteristic.sensor.format();
+        let value = format.clamp(value);
+        let changed = value != characteristic.value;
+        characteristic.value = value;
+
+        let mut buf = [0; 4];
+        let len = format.encode(value, &mut buf);
+        self.table
+            .set_value(characteristic.value_id, &buf[..len])
+            .unwrap();
+        let notify = self.table.notifications_enabled(characteristic.value_id);
+        characteristic.evaluate_triggers(notify, changed, self.now);
+    }
+
+    /// The value of a characteristic, in the unit of the characteristic.
+    pub fn value(&self, id: SensorId) -> i32 {
+        self.characteristics[id.0].value
+    }
+
+    /// Update a temperature characteristic, i.e. `set_temperature(id, 21.5)`.
+    pub fn set_temperature<T: Into<Temperature>>(&mut self, id: SensorId, temperature: T) {
+        self.set_value(id, temperature.into().centi_celsius() as i32);
+    }
+
+    /// Update the first temperature characteristic, i.e. `set_temperature_celsius(21.5)`. Does
+    /// nothing if the service has no temperature characteristic.
+    pub fn set_temperature_celsius<T: Into<Temperature>>(&mut self, celsius: T) {
+        if let Some(id) = self.find(Sensor::Temperature) {
+            self.set_temperature(id, celsius);
+        }
+    }
+
+    /// The first characteristic of the given kind.
+    pub fn find(&self, sensor: Sensor) -> Option<SensorId> {
+        self.characteristics
+            .iter()
+            .position(|c| c.sensor == sensor)
+            .map(SensorId)
+    }
+
+    /// Handle of the value of a characteristic.
+    pub fn value_handle(&self, id: SensorId) -> Handle {
+        self.table.handle(self.characteristics[id.0].value_id)
+    }
+
+    /// Set the current time in seconds, used by the interval trigger conditions. Should be called
+    /// periodically, at least as often as the shortest interval.
+    pub fn update_time(&mut self, seconds: u32) {
+        self.now = seconds;
+        for characteristic in self.characteristics.iter_mut() {
+            let notify = self.table.notifications_enabled(characteristic.value_id);
+            characteristic.evaluate_triggers(notify, false, seconds);
+        }
+    }
+
+    /// Returns true if the client has enabled notifications for a characteristic.
+    pub fn notifications_enabled(&self, id: SensorId) -> bool {
+        self.table
+            .notifications_enabled(self.characteristics[id.0].value_id)
+    }
+
+    /// Returns the next notification to send, if any. The caller is expected to send it as a
+    /// Handle Value Notification, i.e. using `notify_raw` on the attribute server of the rubble
+    /// `Responder`.
+    pub fn take_notification(&mut self) -> Option<Notification> {
+        let characteristic = self
+            .characteristics
+            .iter_mut()
+            .find(|c| c.notification_pending)?;
+        characteristic.notification_pending = false;
+        Some(self.table.notification(characteristic.value_id))
+    }
+
+    pub fn set_sampling_function(&mut self, id: SensorId, sampling_function: SamplingFunction) {
+        self.characteristics[id.0].measurement.sampling_function = sampling_function;
+        self.update_measurement(id);
+    }
+
+    /// Set the period over which the value is measured, in seconds.
+    pub fn set_measurement_period(&mut self, id: SensorId, seconds: u32) {
+        self.characteristics[id.0].measurement.measurement_period = seconds;
+        self.update_measurement(id);
+    }
+
+    /// Set the interval at which the value is updated, in seconds.
+    pub fn set_update_interval(&mut self, id: SensorId, seconds: u32) {
+        self.characteristics[id.0].measurement.update_interval = seconds;
+        self.update_measurement(id);
+    }
+
+    pub fn set_application(&mut self, id: SensorId, application: Application) {
+        self.characteristics[id.0].measurement.application = application;
+        self.update_measurement(id);
+    }
+
+    /// Set the measurement uncertainty, in units of 0.5%.
+    pub fn set_uncertainty(&mut self, id: SensorId, uncertainty: u8) {
+        self.characteristics[id.0].measurement.uncertainty = uncertainty;
+        self.update_measurement(id);
+    }
+
+    /// The contents of the ES Measurement descriptor of a characteristic.
+    pub fn measurement(&self, id: SensorId) -> &Measurement {
+        &self.characteristics[id.0].measurement
+    }
+
+    /// Set the condition of trigger `index` for sending notifications. By default the first
+    /// trigger notifies on change, and the others are inactive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than `MAX_TRIGGERS`.
+    pub fn set_trigger(&mut self, id: SensorId, index: usize, condition: TriggerCondition) {
+        let characteristic = &mut self.characteristics[id.0];
+        let (buf, len) = encode_trigger(characteristic.sensor.format(), &condition);
+        characteristic.triggers[index] = condition;
+        self.table
+            .set_value(characteristic.trigger_ids[index], &buf[..len])
+            .unwrap();
+    }
+
+    /// The condition of trigger `index`.
+    pub fn trigger(&self, id: SensorId, index: usize) -> TriggerCondition {
+        self.characteristics[id.0].triggers[index]
+    }
+
+    /// How the trigger conditions of a characteristic are combined.
+    pub fn trigger_logic(&self, id: SensorId) -> TriggerLogic {
+        self.characteristics[id.0].trigger_logic
+    }
+
+    /// Set how the trigger conditions of a characteristic are combined.
+    pub fn set_trigger_logic(&mut self, id: SensorId, logic: TriggerLogic) {
+        let characteristic = &mut self.characteristics[id.0];
+        characteristic.trigger_logic = logic;
+        self.table
+            .set_value(characteristic.configuration_id, &[logic as u8])
+            .unwrap();
+    }
+
+    /// The Characteristic User Description, if the characteristic has one.
+    pub fn description(&self, id: SensorId) -> Option<&str> {
+        let description = self.characteristics[id.0].description_id?;
+        // Writes are checked to be UTF-8
+        core::str::from_utf8(self.table.value(description)).ok()
+    }
+
+    /// Change the Characteristic User Description. Fails if the characteristic was added without
+    /// one, or the description is longer than `MAX_VALUE_LEN`.
+    pub fn set_description(&mut self, id: SensorId, description: &str) -> Result<(), Error> {
+        let description_id = self.characteristics[id.0]
+            .description_id
+            .ok_or(Error::InvalidValue)?;
+        self.table.set_value(description_id, description.as_bytes())
+    }
+
+    /// Set the function that validates writes from the client to the descriptors, i.e. to limit
+    /// how often notifications can be requested. Writes are applied if the function returns `Ok`.
+    pub fn set_write_callback(&mut self, callback: WriteCallback) {
+        self.write_callback = Some(callback);
+    }
+
+    fn write_descriptor(
+        &mut self,
+        id: SensorId,
+        descriptor: Descriptor,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let format = self.characteristics[id.0].sensor.format();
+        let write = match descriptor {
+            Descriptor::Trigger(index) => {
+                Write::Trigger(index, TriggerCondition::decode(format, data)?)
+            }
+            Descriptor::Configuration => Write::TriggerLogic(TriggerLogic::decode(data)?),
+            Descriptor::Description => {
+                if data.len() > MAX_VALUE_LEN {
+                    return Err(Error::InvalidLength);
+                }
+                Write::Description(core::str::from_utf8(data).map_err(|_| Error::InvalidValue)?)
+            }
+        };
+        if let Some(callback) = self.write_callback {
+            callback(id, &write)?;
+        }
+        match write {
+            Write::Trigger(index, condition) => self.set_trigger(id, index, condition),
+            Write::TriggerLogic(logic) => self.set_trigger_logic(id, logic),
+            Write::Description(description) => self.set_description(id, description)?,
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, handle: Handle, data: &[u8]) -> Result<(), Error> {
+        if let Some(attribute) = self.table.id(handle) {
+            let descriptor = self
+                .characteristics
+                .iter()
+                .enumerate()
+                .find_map(|(i, c)| c.descriptor(attribute).map(|d| (SensorId(i), d)));
+            if let Some((id, descriptor)) = descriptor {
+                return self.write_descriptor(id, descriptor, data);
+            }
+        }
+
+        // Otherwise a Client Characteristic Configuration
+        self.table.write_attr(handle, data)?;
+        for characteristic in self.characteristics.iter_mut() {
+            if !self.table.notifications_enabled(characteristic.value_id) {
+                characteristic.notification_pending = false;
+                characteristic.last_notification = None;
+            }
         }
         Ok(())
     }
 
-    fn is_grouping_attr(&self, uuid: AttUuid) -> bool {
-        uuid == Uuid16(0x2800) // FIXME not characteristics?
+    fn update_measurement(&mut self, id: SensorId) {
+        let characteristic = &self.characteristics[id.0];
+        self.table
+            .set_value(
+                characteristic.measurement_id,
+                &characteristic.measurement.encode(),
+            )
+            .unwrap();
+    }
+}
+
+impl<N: ArrayLength<Characteristic>, A: ArrayLength<Entry>> Default
+    for EnvironmentSensingService<N, A>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_provider!(
+    impl<N: ArrayLength<Characteristic>, A: ArrayLength<Entry>> EnvironmentSensingService<N, A>,
+    write = write
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drogue_microbit_gatt::GattProvider;
+    use rubble::att::HandleRange;
+
+    const TEMPERATURE_VALUE_HANDLE: u16 = 0x0003;
+    const TEMPERATURE_CCCD_HANDLE: u16 = 0x0004;
+
+    fn temperature_service() -> (EnvironmentSensingService, SensorId) {
+        let ess: EnvironmentSensingService = EnvironmentSensingService::new();
+        let id = ess.find(Sensor::Temperature).unwrap();
+        (ess, id)
+    }
+
+    fn subscribe<N: ArrayLength<Characteristic>, A: ArrayLength<Entry>>(
+        ess: &mut EnvironmentSensingService<N, A>,
+        id: SensorId,
+    ) {
+        let value = ess.characteristics[id.0].value_id;
+        let cccd = ess.table.client_config(value).unwrap();
+        ess.write_attr(ess.table.handle(cccd), &[0x01, 0x00])
+            .unwrap();
+    }
+
+    fn handles<N: ArrayLength<Characteristic>, A: ArrayLength<Entry>>(
+        ess: &mut EnvironmentSensingService<N, A>,
+        start: u16,
+        end: u16,
+    ) -> Vec<u16, consts::U32> {
+        let mut handles = Vec::new();
+        ess.for_attrs_in_range(
+            HandleRange::new(Handle::from_raw(start), Handle::from_raw(end)),
+            |_, attr| {
+                handles.push(attr.handle.as_u16()).unwrap();
+                Ok(())
+            },
+        )
+        .unwrap();
+        handles
+    }
+
+    #[test]
+    fn temperature_from_celsius() {
+        assert_eq!(Temperature::from(21.5).centi_celsius(), 2150);
+        assert_eq!(Temperature::from(-0.25).centi_celsius(), -25);
+        assert_eq!(Temperature::from(0.004).centi_celsius(), 0);
+        assert_eq!(Temperature::from(1000.0).centi_celsius(), i16::MAX);
+        assert_eq!(Temperature::from(-1000.0).centi_celsius(), -i16::MAX);
+        assert_eq!(Temperature::from(f32::NAN), Temperature::UNKNOWN);
+    }
+
+    #[test]
+    fn temperature_from_fixed() {
+        assert_eq!(
+            Temperature::from(I30F2::from_num(21.75)).centi_celsius(),
+            2175
+        );
+        assert_eq!(
+            Temperature::from(I30F2::from_num(-3.25)).centi_celsius(),
+            -325
+        );
+        assert_eq!(Temperature::from(I30F2::MAX).centi_celsius(), i16::MAX);
+        assert_eq!(Temperature::from(I30F2::MIN).centi_celsius(), -i16::MAX);
+    }
+
+    #[test]
+    fn temperature_encoding() {
+        assert_eq!(Temperature::from(21.5).to_le_bytes(), [0x66, 0x08]);
+        assert_eq!(Temperature::from(-1.0).to_le_bytes(), [0x9C, 0xFF]);
+        assert_eq!(Temperature::UNKNOWN.to_le_bytes(), [0x00, 0x80]);
+    }
+
+    #[test]
+    fn notify_when_subscribed() {
+        let (mut ess, id) = temperature_service();
+        ess.set_temperature_celsius(20.0);
+        assert_eq!(ess.take_notification(), None);
+
+        ess.write_attr(Handle::from_raw(TEMPERATURE_CCCD_HANDLE), &[0x01, 0x00])
+            .unwrap();
+        assert!(ess.notifications_enabled(id));
+        ess.set_temperature_celsius(20.5);
+        let notification = ess.take_notification().unwrap();
+        assert_eq!(
+            notification.handle(),
+            Handle::from_raw(TEMPERATURE_VALUE_HANDLE)
+        );
+        assert_eq!(notification.value(), &[0x02, 0x08]);
+        assert_eq!(ess.take_notification(), None);
+
+        // Unchanged values are not notified
+        ess.set_temperature_celsius(20.5);
+        assert_eq!(ess.take_notification(), None);
+
+        ess.write_attr(Handle::from_raw(TEMPERATURE_CCCD_HANDLE), &[0x00, 0x00])
+            .unwrap();
+        ess.set_temperature_celsius(21.0);
+        assert_eq!(ess.take_notification(), None);
+    }
+
+    #[test]
+    fn invalid_cccd_writes() {
+        let (mut ess, id) = temperature_service();
+        assert_eq!(
+            ess.write_attr(Handle::from_raw(TEMPERATURE_CCCD_HANDLE), &[0x01]),
+            Err(Error::InvalidLength)
+        );
+        assert_eq!(
+            ess.write_attr(Handle::from_raw(TEMPERATURE_CCCD_HANDLE), &[0x02, 0x00]),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            ess.write_attr(Handle::from_raw(TEMPERATURE_VALUE_HANDLE), &[0x00, 0x00]),
+            Err(Error::InvalidValue)
+        );
+        assert!(!ess.notifications_enabled(id));
     }
 
-    fn group_end(&self, handle: Handle) -> Option<&Attribute<dyn AttrValue>> {
-        match handle.as_u16() {
-            0x0001 => Some(&self.attributes[2]),
-            0x0002 => Some(&self.attributes[2]),
-            _ => None,
+    #[test]
+    fn set_temperature() {
+        let (mut ess, id) = temperature_service();
+        ess.set_temperature_celsius(23.25);
+        assert_eq!(
+            ess.table.value(ess.characteristics[id.0].value_id),
+            &[0x15, 0x09]
+        );
+        assert_eq!(ess.value(id), 2325);
+    }
+
+    #[test]
+    fn measurement_descriptor() {
+        let (mut ess, id) = temperature_service();
+        ess.set_sampling_function(id, SamplingFunction::ArithmeticMean);
+        ess.set_measurement_period(id, 10);
+        ess.set_update_interval(id, 1);
+        ess.set_application(id, Application::INDOOR);
+        ess.set_uncertainty(id, 2);
+        assert_eq!(
+            ess.table.value(ess.characteristics[id.0].measurement_id),
+            &[0x00, 0x00, 0x02, 10, 0, 0, 1, 0, 0, 0x14, 2]
+        );
+    }
+
+    #[test]
+    fn trigger_descriptors() {
+        let (mut ess, id) = temperature_service();
+        let trigger = |ess: &EnvironmentSensingService, index: usize| {
+            let id = ess.characteristics[id.0].trigger_ids[index];
+            let mut value = [0; MAX_TRIGGER_LEN];
+            value[..ess.table.value(id).len()].copy_from_slice(ess.table.value(id));
+            (value, ess.table.value(id).len())
+        };
+        let configuration = |ess: &EnvironmentSensingService| {
+            ess.table.value(ess.characteristics[id.0].configuration_id)[0]
+        };
+        assert_eq!(trigger(&ess, 0), ([0x03, 0, 0, 0, 0], 1));
+        assert_eq!(trigger(&ess, 1), ([0x00, 0, 0, 0, 0], 1));
+        assert_eq!(configuration(&ess), 0x01);
+
+        ess.set_trigger(id, 1, TriggerCondition::FixedInterval(60));
+        ess.set_trigger_logic(id, TriggerLogic::And);
+        assert_eq!(trigger(&ess, 1), ([0x01, 60, 0, 0, 0], 4));
+        assert_eq!(configuration(&ess), 0x00);
+    }
+
+    #[test]
+    fn fixed_interval_trigger() {
+        let (mut ess, id) = temperature_service();
+        ess.set_trigger(id, 0, TriggerCondition::FixedInterval(10));
+        subscribe(&mut ess, id);
+
+        ess.update_time(1);
+        assert!(ess.take_notification().is_some());
+        ess.set_temperature_celsius(20.0);
+        ess.update_time(5);
+        assert_eq!(ess.take_notification(), None);
+        ess.update_time(11);
+        assert!(ess.take_notification().is_some());
+    }
+
+    #[test]
+    fn threshold_trigger() {
+        let (mut ess, id) = temperature_service();
+        ess.set_trigger(id, 0, TriggerCondition::GreaterThan(2500));
+        subscribe(&mut ess, id);
+
+        ess.set_temperature_celsius(24.0);
+        assert_eq!(ess.take_notification(), None);
+        ess.set_temperature_celsius(26.0);
+        assert!(ess.take_notification().is_some());
+    }
+
+    #[test]
+    fn combined_triggers() {
+        let (mut ess, id) = temperature_service();
+        ess.set_trigger(id, 0, TriggerCondition::OnChange);
+        ess.set_trigger(id, 1, TriggerCondition::LessThan(0));
+        ess.set_trigger_logic(id, TriggerLogic::And);
+        subscribe(&mut ess, id);
+
+        ess.set_temperature_celsius(1.0);
+        assert_eq!(ess.take_notification(), None);
+        ess.set_temperature_celsius(-1.0);
+        assert!(ess.take_notification().is_some());
+
+        ess.set_trigger_logic(id, TriggerLogic::Or);
+        ess.set_temperature_celsius(1.0);
+        assert!(ess.take_notification().is_some());
+    }
+
+    #[test]
+    fn handles_are_allocated_in_order() {
+        let mut builder = EnvironmentSensingService::<consts::U2, consts::U15>::builder();
+        let humidity = builder.add(Sensor::Humidity).unwrap();
+        let pressure = builder.add(Sensor::Pressure).unwrap();
+        assert_eq!(builder.add(Sensor::Elevation), None);
+        let mut ess = builder.build();
+
+        assert_eq!(
+            &handles(&mut ess, 0, 0xFFFF)[..],
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+        );
+        assert_eq!(ess.value_handle(humidity), Handle::from_raw(3));
+        assert_eq!(ess.value_handle(pressure), Handle::from_raw(10));
+
+        // Groups end at the last descriptor
+        let end = |handle| {
+            ess.group_end(Handle::from_raw(handle))
+                .map(|a| a.handle.as_u16())
+        };
+        assert_eq!(end(1), Some(15));
+        assert_eq!(end(2), Some(8));
+        assert_eq!(end(9), Some(15));
+        assert_eq!(end(3), None);
+        assert!(!ess.is_grouping_attr(Uuid16(0x2803).into()));
+
+        ess.set_value(pressure, 1_013_250);
+        assert_eq!(
+            ess.table.value(ess.characteristics[pressure.0].value_id),
+            &[0x02, 0x76, 0x0F, 0x00]
+        );
+    }
+
+    #[test]
+    fn table_too_small() {
+        let mut builder = EnvironmentSensingService::<consts::U2, consts::U10>::builder();
+        assert!(builder.add(Sensor::Humidity).is_some());
+        assert_eq!(builder.add(Sensor::Pressure), None);
+        let mut ess = builder.build();
+        assert_eq!(&handles(&mut ess, 1, 0xFFFF)[..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn notifications_per_characteristic() {
+        let mut builder = EnvironmentSensingService::<consts::U2, consts::U15>::builder();
+        let temperature = builder.add(Sensor::Temperature).unwrap();
+        let humidity = builder.add(Sensor::Humidity).unwrap();
+        let mut ess = builder.build();
+
+        subscribe(&mut ess, humidity);
+        assert!(!ess.notifications_enabled(temperature));
+        assert!(ess.notifications_enabled(humidity));
+
+        ess.set_temperature(temperature, 21.0);
+        ess.set_value(humidity, 4550);
+        let notification = ess.take_notification().unwrap();
+        assert_eq!(notification.handle(), ess.value_handle(humidity));
+        assert_eq!(notification.value(), &[0xC6, 0x11]);
+        assert_eq!(ess.take_notification(), None);
+    }
+
+    fn write_descriptor<N: ArrayLength<Characteristic>, A: ArrayLength<Entry>>(
+        ess: &mut EnvironmentSensingService<N, A>,
+        id: AttributeId,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        ess.write_attr(ess.table.handle(id), data)
+    }
+
+    #[test]
+    fn write_triggers() {
+        let (mut ess, id) = temperature_service();
+        let triggers = ess.characteristics[id.0].trigger_ids;
+        let configuration = ess.characteristics[id.0].configuration_id;
+        assert_eq!(
+            ess.attr_access_permissions(ess.table.handle(triggers[0])),
+            AttributeAccessPermissions::ReadableAndWriteable
+        );
+
+        write_descriptor(&mut ess, triggers[1], &[0x01, 60, 0, 0]).unwrap();
+        assert_eq!(ess.trigger(id, 1), TriggerCondition::FixedInterval(60));
+        assert_eq!(ess.table.value(triggers[1]), &[0x01, 60, 0, 0]);
+        write_descriptor(&mut ess, configuration, &[0x00]).unwrap();
+        assert_eq!(ess.trigger_logic(id), TriggerLogic::And);
+
+        assert_eq!(
+            write_descriptor(&mut ess, triggers[0], &[0x04, 0x00]),
+            Err(Error::InvalidLength)
+        );
+        assert_eq!(
+            write_descriptor(&mut ess, configuration, &[0x05]),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(ess.trigger(id, 0), TriggerCondition::OnChange);
+        assert_eq!(ess.table.value(triggers[0]), &[0x03]);
+
+        // Written triggers gate notifications
+        write_descriptor(&mut ess, triggers[0], &[0x06, 0xC4, 0x09]).unwrap();
+        write_descriptor(&mut ess, triggers[1], &[0x00]).unwrap();
+        subscribe(&mut ess, id);
+        ess.set_temperature_celsius(25.0);
+        assert_eq!(ess.take_notification(), None);
+        ess.set_temperature_celsius(25.5);
+        assert!(ess.take_notification().is_some());
+    }
+
+    #[test]
+    fn write_callback() {
+        fn limit_interval(_: SensorId, write: &Write) -> Result<(), Error> {
+            match write {
+                Write::Trigger(_, TriggerCondition::FixedInterval(s)) if *s < 10 => {
+                    Err(Error::InvalidValue)
+                }
+                Write::Description("") => Err(Error::InvalidValue),
+                _ => Ok(()),
+            }
         }
+
+        let mut builder = EnvironmentSensingService::<consts::U1, consts::U9>::builder();
+        let id = builder
+            .add_with_description(Sensor::Temperature, "Outdoor")
+            .unwrap();
+        let mut ess = builder.build();
+        ess.set_write_callback(limit_interval);
+        let trigger = ess.characteristics[id.0].trigger_ids[0];
+        let description = ess.characteristics[id.0].description_id.unwrap();
+
+        assert_eq!(
+            write_descriptor(&mut ess, trigger, &[0x01, 5, 0, 0]),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(ess.trigger(id, 0), TriggerCondition::OnChange);
+        write_descriptor(&mut ess, trigger, &[0x01, 10, 0, 0]).unwrap();
+        assert_eq!(ess.trigger(id, 0), TriggerCondition::FixedInterval(10));
+
+        assert_eq!(ess.description(id), Some("Outdoor"));
+        write_descriptor(&mut ess, description, b"Greenhouse").unwrap();
+        assert_eq!(ess.description(id), Some("Greenhouse"));
+        assert_eq!(
+            write_descriptor(&mut ess, description, b""),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            write_descriptor(&mut ess, description, &[0xFF]),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(ess.description(id), Some("Greenhouse"));
+    }
+
+    #[test]
+    fn description_needs_room() {
+        let mut builder = EnvironmentSensingService::<consts::U2, consts::U15>::builder();
+        let id = builder.add(Sensor::Temperature).unwrap();
+        assert_eq!(
+            builder.add_with_description(Sensor::Humidity, "Inside"),
+            None
+        );
+        let mut ess = builder.build();
+        assert_eq!(ess.description(id), None);
+        assert_eq!(ess.set_description(id, "Outside"), Err(Error::InvalidValue));
+    }
+
+    #[test]
+    fn relocated_service() {
+        let (mut ess, id) = temperature_service();
+        ess.relocate(10);
+        assert_eq!(ess.value_handle(id), Handle::from_raw(12));
+        assert_eq!(&handles(&mut ess, 0, 12)[..], &[10, 11, 12]);
+
+        ess.write_attr(Handle::from_raw(13), &[0x01, 0x00]).unwrap();
+        ess.set_temperature_celsius(20.0);
+        let notification = ess.take_notification().unwrap();
+        assert_eq!(notification.handle(), Handle::from_raw(12));
     }
 }
@@ -0,0 +1,191 @@
+//! The characteristics that can be added to the Environmental Sensing Service.
+
+use rubble::uuid::Uuid16;
+
+/// Encoding of a characteristic value, as little-endian integers in the unit of the
+/// characteristic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    U8,
+    S8,
+    U16,
+    S16,
+    U24,
+    S24,
+    U32,
+}
+
+impl Format {
+    /// Encoded size in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            Format::U8 | Format::S8 => 1,
+            Format::U16 | Format::S16 => 2,
+            Format::U24 | Format::S24 => 3,
+            Format::U32 => 4,
+        }
+    }
+
+    fn range(&self) -> (i32, i32) {
+        match self {
+            Format::U8 => (0, u8::MAX as i32),
+            Format::S8 => (i8::MIN as i32, i8::MAX as i32),
+            Format::U16 => (0, u16::MAX as i32),
+            Format::S16 => (i16::MIN as i32, i16::MAX as i32),
+            Format::U24 => (0, 0x00FF_FFFF),
+            Format::S24 => (-0x0080_0000, 0x007F_FFFF),
+            Format::U32 => (0, i32::MAX),
+        }
+    }
+
+    /// Saturate `value` to the range of the format.
+    pub(crate) fn clamp(&self, value: i32) -> i32 {
+        let (min, max) = self.range();
+        value.max(min).min(max)
+    }
+
+    /// Decode a value, or `None` if `data` has the wrong size or the value does not fit in an
+    /// `i32`.
+    pub(crate) fn decode(&self, data: &[u8]) -> Option<i32> {
+        if data.len() != self.size() {
+            return None;
+        }
+        let mut buf = [0; 4];
+        buf[..data.len()].copy_from_slice(data);
+        let signed = matches!(self, Format::S8 | Format::S16 | Format::S24);
+        if signed && data[data.len() - 1] & 0x80 != 0 {
+            // Sign extend
+            for b in buf[data.len()..].iter_mut() {
+                *b = 0xFF;
+            }
+        }
+        let value = i32::from_le_bytes(buf);
+        if *self == Format::U32 && value < 0 {
+            return None;
+        }
+        Some(value)
+    }
+
+    /// Encode `value` into the start of `buf`, returning the number of bytes used.
+    pub(crate) fn encode(&self, value: i32, buf: &mut [u8]) -> usize {
+        let len = self.size();
+        buf[..len].copy_from_slice(&self.clamp(value).to_le_bytes()[..len]);
+        len
+    }
+}
+
+/// A characteristic of the Environmental Sensing Service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sensor {
+    /// 0.01 degrees Celsius.
+    Temperature,
+    /// 0.01 percent relative humidity.
+    Humidity,
+    /// 0.1 Pascal.
+    Pressure,
+    /// 0.01 metre.
+    Elevation,
+    /// UV index.
+    UvIndex,
+    /// 0.1 Watt per square metre.
+    Irradiance,
+    /// Degrees Celsius.
+    DewPoint,
+    /// Degrees Celsius.
+    HeatIndex,
+    /// Degrees Celsius.
+    WindChill,
+    /// 0.01 metre per second.
+    TrueWindSpeed,
+    /// 0.01 degrees.
+    TrueWindDirection,
+    /// Millimetre.
+    Rainfall,
+    /// Count per cubic metre.
+    PollenConcentration,
+    /// 0.01 degrees.
+    MagneticDeclination,
+    /// Any other characteristic, for sensors not covered by the above.
+    Custom { uuid: Uuid16, format: Format },
+}
+
+impl Sensor {
+    pub fn uuid(&self) -> Uuid16 {
+        match *self {
+            Sensor::Temperature => Uuid16(0x2A6E),
+            Sensor::Humidity => Uuid16(0x2A6F),
+            Sensor::Pressure => Uuid16(0x2A6D),
+            Sensor::Elevation => Uuid16(0x2A6C),
+            Sensor::UvIndex => Uuid16(0x2A76),
+            Sensor::Irradiance => Uuid16(0x2A77),
+            Sensor::DewPoint => Uuid16(0x2A7B),
+            Sensor::HeatIndex => Uuid16(0x2A7A),
+            Sensor::WindChill => Uuid16(0x2A79),
+            Sensor::TrueWindSpeed => Uuid16(0x2A70),
+            Sensor::TrueWindDirection => Uuid16(0x2A71),
+            Sensor::Rainfall => Uuid16(0x2A78),
+            Sensor::PollenConcentration => Uuid16(0x2A75),
+            Sensor::MagneticDeclination => Uuid16(0x2A2C),
+            Sensor::Custom { uuid, .. } => uuid,
+        }
+    }
+
+    pub fn format(&self) -> Format {
+        match *self {
+            Sensor::Temperature => Format::S16,
+            Sensor::Humidity => Format::U16,
+            Sensor::Pressure => Format::U32,
+            Sensor::Elevation => Format::S24,
+            Sensor::UvIndex => Format::U8,
+            Sensor::Irradiance => Format::U16,
+            Sensor::DewPoint | Sensor::HeatIndex | Sensor::WindChill => Format::S8,
+            Sensor::TrueWindSpeed => Format::U16,
+            Sensor::TrueWindDirection => Format::U16,
+            Sensor::Rainfall => Format::U16,
+            Sensor::PollenConcentration => Format::U24,
+            Sensor::MagneticDeclination => Format::U16,
+            Sensor::Custom { format, .. } => format,
+        }
+    }
+
+    /// Value before the first update.
+    pub(crate) fn initial_value(&self) -> i32 {
+        match self {
+            Sensor::Temperature => i16::MIN as i32,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_formats() {
+        let mut buf = [0; 4];
+        assert_eq!(Format::S24.encode(-2, &mut buf), 3);
+        assert_eq!(buf[..3], [0xFE, 0xFF, 0xFF]);
+        assert_eq!(Format::U32.encode(1_013_250, &mut buf), 4);
+        assert_eq!(buf, [0x02, 0x76, 0x0F, 0x00]);
+
+        // Values are saturated
+        assert_eq!(Format::U8.encode(300, &mut buf), 1);
+        assert_eq!(buf[0], 0xFF);
+        assert_eq!(Format::U16.encode(-1, &mut buf), 2);
+        assert_eq!(buf[..2], [0x00, 0x00]);
+    }
+
+    #[test]
+    fn decode_formats() {
+        assert_eq!(Format::S24.decode(&[0xFE, 0xFF, 0xFF]), Some(-2));
+        assert_eq!(Format::U24.decode(&[0xFE, 0xFF, 0xFF]), Some(0x00FF_FFFE));
+        assert_eq!(Format::S16.decode(&[0x0C, 0xFE]), Some(-500));
+        assert_eq!(
+            Format::U32.decode(&[0x02, 0x76, 0x0F, 0x00]),
+            Some(1_013_250)
+        );
+        assert_eq!(Format::U32.decode(&[0xFF, 0xFF, 0xFF, 0xFF]), None);
+        assert_eq!(Format::S16.decode(&[0x00]), None);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::table::{Entry, GattTable};
+use heapless::ArrayLength;
+use rubble::att::{
+    AttUuid, AttrValue, Attribute, AttributeAccessPermissions, AttributeProvider, Handle,
+    HandleRange,
+};
+use rubble::Error;
+
+/// Callback of `GattProvider::visit_attrs`.
+pub type Visitor<'a> = dyn FnMut(&Attribute<dyn AttrValue>) -> Result<(), Error> + 'a;
+
+/// An `AttributeProvider` with a contiguous handle range that can be moved, so that it can be
+/// combined with other providers.
+pub trait GattProvider: AttributeProvider {
+    /// The first handle of the provider.
+    fn first_handle(&self) -> u16;
+
+    /// The last handle of the provider, or one less than the first if it has no attributes.
+    fn last_handle(&self) -> u16;
+
+    /// Move the attributes to consecutive handles starting at `first`.
+    fn relocate(&mut self, first: u16);
+
+    /// Invoke `f` for the attributes in `range`. Unlike `for_attrs_in_range` this does not need
+    /// mutable access, so providers can be visited from a `Composite`.
+    fn visit_attrs(&self, range: HandleRange, f: &mut Visitor<'_>) -> Result<(), Error>;
+
+    /// Combine with `other`, which is relocated to follow the attributes of this provider.
+    fn chain<B: GattProvider>(self, other: B) -> Composite<Self, B>
+    where
+        Self: Sized,
+    {
+        Composite::new(self, other)
+    }
+}
+
+/// Two providers served as one, i.e. `ess.chain(dis).chain(battery)`.
+///
+/// The second provider is relocated to the handles following the first, and requests are
+/// delegated to the provider owning the handle.
+pub struct Composite<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: GattProvider, B: GattProvider> Composite<A, B> {
+    pub fn new(first: A, mut second: B) -> Self {
+        second.relocate(next_handle(&first));
+        Self { first, second }
+    }
+
+    pub fn first(&mut self) -> &mut A {
+        &mut self.first
+    }
+
+    pub fn second(&mut self) -> &mut B {
+        &mut self.second
+    }
+
+    fn owns_first(&self, handle: Handle) -> bool {
+        handle.as_u16() <= self.first.last_handle()
+    }
+}
+
+impl<A: GattProvider, B: GattProvider> AttributeProvider for Composite<A, B> {
+    fn for_attrs_in_range(
+        &mut self,
+        range: HandleRange,
+        mut f: impl FnMut(&Self, &Attribute<dyn AttrValue>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let this = &*self;
+        this.visit_attrs(range, &mut |attr| f(this, attr))
+    }
+
+    fn is_grouping_attr(&self, uuid: AttUuid) -> bool {
+        self.first.is_grouping_attr(uuid) || self.second.is_grouping_attr(uuid)
+    }
+
+    fn group_end(&self, handle: Handle) -> Option<&Attribute<dyn AttrValue>> {
+        if self.owns_first(handle) {
+            self.first.group_end(handle)
+        } else {
+            self.second.group_end(handle)
+        }
+    }
+
+    fn attr_access_permissions(&self, handle: Handle) -> AttributeAccessPermissions {
+        if self.owns_first(handle) {
+            self.first.attr_access_permissions(handle)
+        } else {
+            self.second.attr_access_permissions(handle)
+        }
+    }
+
+    fn write_attr(&mut self, handle: Handle, data: &[u8]) -> Result<(), Error> {
+        if self.owns_first(handle) {
+            self.first.write_attr(handle, data)
+        } else {
+            self.second.write_attr(handle, data)
+        }
+    }
+}
+
+impl<A: GattProvider, B: GattProvider> GattProvider for Composite<A, B> {
+    fn first_handle(&self) -> u16 {
+        self.first.first_handle()
+    }
+
+    fn last_handle(&self) -> u16 {
+        self.second.last_handle()
+    }
+
+    fn relocate(&mut self, first: u16) {
+        self.first.relocate(first);
+        self.second.relocate(next_handle(&self.first));
+    }
+
+    fn visit_attrs(&self, range: HandleRange, f: &mut Visitor<'_>) -> Result<(), Error> {
+        self.first.visit_attrs(range, f)?;
+        self.second.visit_attrs(range, f)
+    }
+}
+
+/// The handle following the attributes of `provider`.
+fn next_handle<P: GattProvider>(provider: &P) -> u16 {
+    provider
+        .last_handle()
+        .checked_add(1)
+        .expect("no handles left for the second provider")
+}
+
+impl<N: ArrayLength<Entry>> GattProvider for GattTable<N> {
+    fn first_handle(&self) -> u16 {
+        GattTable::first_handle(self)
+    }
+
+    fn last_handle(&self) -> u16 {
+        GattTable::last_handle(self)
+    }
+
+    fn relocate(&mut self, first: u16) {
+        GattTable::relocate(self, first)
+    }
+
+    fn visit_attrs(&self, range: HandleRange, f: &mut Visitor<'_>) -> Result<(), Error> {
+        for attr in self.attributes_in(range) {
+            f(attr)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Properties;
+    use heapless::{consts, Vec};
+    use rubble::uuid::Uuid16;
+
+    fn service(uuid: u16, characteristic: u16) -> GattTable<consts::U4> {
+        let mut builder = GattTable::builder();
+        builder.service(Uuid16(uuid)).unwrap();
+        builder
+            .characteristic(
+                Uuid16(characteristic),
+                Properties::READ | Properties::NOTIFY,
+                &[0],
+            )
+            .unwrap();
+        builder.build()
+    }
+
+    fn handles<P: AttributeProvider>(
+        provider: &mut P,
+        start: u16,
+        end: u16,
+    ) -> Vec<u16, consts::U16> {
+        let mut handles = Vec::new();
+        provider
+            .for_attrs_in_range(
+                HandleRange::new(Handle::from_raw(start), Handle::from_raw(end)),
+                |_, attr| {
+                    handles.push(attr.handle.as_u16()).unwrap();
+                    Ok(())
+                },
+            )
+            .unwrap();
+        handles
+    }
+
+    #[test]
+    fn relocates_second_provider() {
+        let mut composite = service(0x180F, 0x2A19)
+            .chain(service(0x181A, 0x2A6E))
+            .chain(service(0x1809, 0x2A1C));
+        assert_eq!(composite.first_handle(), 1);
+        assert_eq!(composite.last_handle(), 12);
+        assert_eq!(
+            &handles(&mut composite, 0, 0xFFFF)[..],
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]
+        );
+        assert_eq!(&handles(&mut composite, 4, 6)[..], &[4, 5, 6]);
+
+        // Value handles in the declarations follow the relocation
+        let second = composite.first().second();
+        let declaration = second.id(Handle::from_raw(6)).unwrap();
+        assert_eq!(second.value(declaration), &[0x12, 0x07, 0x00, 0x6E, 0x2A]);
+        assert_eq!(second.id(Handle::from_raw(4)), None);
+    }
+
+    #[test]
+    fn delegates_by_handle() {
+        let mut composite = service(0x180F, 0x2A19).chain(service(0x181A, 0x2A6E));
+        let end = |composite: &Composite<_, _>, handle| {
+            composite
+                .group_end(Handle::from_raw(handle))
+                .map(|a: &Attribute<dyn AttrValue>| a.handle.as_u16())
+        };
+        assert_eq!(end(&composite, 1), Some(4));
+        assert_eq!(end(&composite, 5), Some(8));
+        assert_eq!(end(&composite, 6), Some(8));
+
+        composite
+            .write_attr(Handle::from_raw(8), &[0x01, 0x00])
+            .unwrap();
+        let second = composite.second();
+        let value = second.id(Handle::from_raw(7)).unwrap();
+        assert!(second.notifications_enabled(value));
+        assert_eq!(second.notification(value).handle(), Handle::from_raw(7));
+        let first = composite.first();
+        assert!(!first.notifications_enabled(first.id(Handle::from_raw(3)).unwrap()));
+        assert_eq!(
+            composite.attr_access_permissions(Handle::from_raw(8)),
+            AttributeAccessPermissions::ReadableAndWriteable
+        );
+        assert!(composite.is_grouping_attr(Uuid16(0x2800).into()));
+    }
+
+    #[test]
+    fn empty_first_provider() {
+        let mut composite = GattTable::<consts::U4>::builder()
+            .build()
+            .chain(service(0x180F, 0x2A19));
+        assert_eq!(composite.first().last_handle(), 0);
+        assert_eq!(composite.first_handle(), 1);
+        assert_eq!(composite.last_handle(), 4);
+        assert_eq!(&handles(&mut composite, 1, 0xFFFF)[..], &[1, 2, 3, 4]);
+
+        // The empty table owns no handles, so requests go to the second provider
+        let end = composite
+            .group_end(Handle::from_raw(1))
+            .map(|a| a.handle.as_u16());
+        assert_eq!(end, Some(4));
+        assert_eq!(
+            composite.attr_access_permissions(Handle::from_raw(3)),
+            AttributeAccessPermissions::Readable
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Building blocks for GATT services on top of rubble.
+//!
+//! A `GattTable` is built from a declarative description of services, characteristics and
+//! descriptors. The table allocates handles, encodes the declarations and implements
+//! `AttributeProvider`, including grouping and Client Characteristic Configuration writes.
+//!
+//! Providers implementing `GattProvider` can be combined with `chain` to serve several services
+//! from one device.
+#![no_std]
+
+use core::ops::BitOr;
+use rubble::uuid::Uuid16;
+
+/// Implement `AttributeProvider` and `GattProvider` for a service by delegating to its `table`
+/// field, which must be a `GattTable`.
+///
+/// Writes go to the table as well, unless a write hook is given with `write = method`, in which
+/// case they go to `self.method(handle, data)` so the service can act on them. Generic services
+/// list their parameters after `impl`.
+///
+/// ```
+/// use drogue_microbit_gatt::{impl_provider, GattTable};
+/// use heapless::{consts, ArrayLength};
+/// use rubble::att::{AttributeProvider, Handle};
+/// use rubble::Error;
+///
+/// struct Service {
+///     table: GattTable<consts::U4>,
+/// }
+///
+/// impl_provider!(Service);
+///
+/// struct Logger<N: ArrayLength<u8>> {
+///     table: GattTable<consts::U4>,
+///     log: heapless::Vec<u8, N>,
+/// }
+///
+/// impl<N: ArrayLength<u8>> Logger<N> {
+///     fn write(&mut self, handle: Handle, data: &[u8]) -> Result<(), Error> {
+///         self.log.extend_from_slice(data).map_err(|_| Error::InvalidLength)?;
+///         self.table.write_attr(handle, data)
+///     }
+/// }
+///
+/// impl_provider!(impl<N: ArrayLength<u8>> Logger<N>, write = write);
+/// ```
+#[macro_export]
+macro_rules! impl_provider {
+    (@write $this:ident, $handle:ident, $data:ident,) => {
+        rubble::att::AttributeProvider::write_attr(&mut $this.table, $handle, $data)
+    };
+    (@write $this:ident, $handle:ident, $data:ident, $write:ident) => {
+        $this.$write($handle, $data)
+    };
+    (impl<$($param:ident: $bound:path),*> $service:ty $(, write = $write:ident)?) => {
+        impl<$($param: $bound),*> rubble::att::AttributeProvider for $service {
+            fn for_attrs_in_range(
+                &mut self,
+                range: rubble::att::HandleRange,
+                mut f: impl FnMut(
+                    &Self,
+                    &rubble::att::Attribute<dyn rubble::att::AttrValue>,
+                ) -> Result<(), rubble::Error>,
+            ) -> Result<(), rubble::Error> {
+                let this = &*self;
+                for attr in this.table.attributes_in(range) {
+                    f(this, attr)?;
+                }
+                Ok(())
+            }
+
+            fn is_grouping_attr(&self, uuid: rubble::att::AttUuid) -> bool {
+                rubble::att::AttributeProvider::is_grouping_attr(&self.table, uuid)
+            }
+
+            fn group_end(
+                &self,
+                handle: rubble::att::Handle,
+            ) -> Option<&rubble::att::Attribute<dyn rubble::att::AttrValue>> {
+                rubble::att::AttributeProvider::group_end(&self.table, handle)
+            }
+
+            fn attr_access_permissions(
+                &self,
+                handle: rubble::att::Handle,
+            ) -> rubble::att::AttributeAccessPermissions {
+                rubble::att::AttributeProvider::attr_access_permissions(&self.table, handle)
+            }
+
+            fn write_attr(
+                &mut self,
+                handle: rubble::att::Handle,
+                data: &[u8],
+            ) -> Result<(), rubble::Error> {
+                $crate::impl_provider!(@write self, handle, data, $($write)?)
+            }
+        }
+
+        impl<$($param: $bound),*> $crate::GattProvider for $service {
+            fn first_handle(&self) -> u16 {
+                $crate::GattProvider::first_handle(&self.table)
+            }
+
+            fn last_handle(&self) -> u16 {
+                $crate::GattProvider::last_handle(&self.table)
+            }
+
+            fn relocate(&mut self, first: u16) {
+                $crate::GattProvider::relocate(&mut self.table, first)
+            }
+
+            fn visit_attrs(
+                &self,
+                range: rubble::att::HandleRange,
+                f: &mut $crate::Visitor<'_>,
+            ) -> Result<(), rubble::Error> {
+                $crate::GattProvider::visit_attrs(&self.table, range, f)
+            }
+        }
+    };
+    ($service:ty $(, write = $write:ident)?) => {
+        $crate::impl_provider!(impl<> $service $(, write = $write)?);
+    };
+}
+
+mod composite;
+mod table;
+mod value;
+
+pub use composite::{Composite, GattProvider, Visitor};
+pub use table::{AttributeId, BuildError, Builder, Entry, GattTable, Notification};
+pub use value::{AttributeValue, MAX_VALUE_LEN};
+
+pub const PRIMARY_SERVICE_UUID: Uuid16 = Uuid16(0x2800);
+pub const SECONDARY_SERVICE_UUID: Uuid16 = Uuid16(0x2801);
+pub const CHARACTERISTIC_UUID: Uuid16 = Uuid16(0x2803);
+pub const CHARACTERISTIC_USER_DESCRIPTION_UUID: Uuid16 = Uuid16(0x2901);
+pub const CLIENT_CHARACTERISTIC_CONFIGURATION_UUID: Uuid16 = Uuid16(0x2902);
+
+/// Characteristic properties, as encoded in the characteristic declaration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Properties(u8);
+
+impl Properties {
+    pub const NONE: Properties = Properties(0x00);
+    pub const BROADCAST: Properties = Properties(0x01);
+    pub const READ: Properties = Properties(0x02);
+    pub const WRITE_WITHOUT_RESPONSE: Properties = Properties(0x04);
+    pub const WRITE: Properties = Properties(0x08);
+    pub const NOTIFY: Properties = Properties(0x10);
+    pub const INDICATE: Properties = Properties(0x20);
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns true if all properties in `other` are set.
+    pub const fn contains(&self, other: Properties) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_readable(&self) -> bool {
+        self.contains(Properties::READ)
+    }
+
+    pub fn is_writeable(&self) -> bool {
+        self.0 & (Properties::WRITE.0 | Properties::WRITE_WITHOUT_RESPONSE.0) != 0
+    }
+
+    /// Returns true if the characteristic needs a Client Characteristic Configuration.
+    pub fn is_configurable(&self) -> bool {
+        self.0 & (Properties::NOTIFY.0 | Properties::INDICATE.0) != 0
+    }
+}
+
+impl BitOr for Properties {
+    type Output = Properties;
+
+    fn bitor(self, rhs: Properties) -> Properties {
+        Properties(self.0 | rhs.0)
+    }
+}
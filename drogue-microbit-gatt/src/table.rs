@@ -0,0 +1,646 @@
+use crate::value::AttributeValue;
+use crate::{
+    Properties, CHARACTERISTIC_UUID, CLIENT_CHARACTERISTIC_CONFIGURATION_UUID,
+    PRIMARY_SERVICE_UUID, SECONDARY_SERVICE_UUID,
+};
+use heapless::{ArrayLength, Vec};
+use rubble::att::{
+    AttUuid, AttrValue, Attribute, AttributeAccessPermissions, AttributeProvider, Handle,
+    HandleRange,
+};
+use rubble::bytes::{ByteWriter, ToBytes};
+use rubble::Error;
+
+/// Identifies an attribute of a `GattTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributeId(usize);
+
+/// Errors when building a `GattTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    /// The table has no room for more attributes.
+    TableFull,
+    /// The value is longer than `MAX_VALUE_LEN`.
+    ValueTooLong,
+    /// A characteristic was added before any service.
+    NoService,
+    /// A descriptor was added before any characteristic of the current service.
+    NoCharacteristic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Service,
+    Declaration,
+    Value(Properties),
+    ClientConfig(Properties),
+    Descriptor(AttributeAccessPermissions),
+}
+
+/// An attribute of a `GattTable`.
+pub struct Entry {
+    attribute: Attribute<AttributeValue>,
+    kind: Kind,
+    /// Index of the last attribute in the group of a service or characteristic declaration.
+    group_end: usize,
+}
+
+/// A notification to be sent to the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Notification {
+    handle: Handle,
+    value: AttributeValue,
+}
+
+impl Notification {
+    /// Handle of the characteristic value.
+    pub fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    pub fn value(&self) -> &[u8] {
+        self.value.as_slice()
+    }
+}
+
+/// A table of GATT attributes with handles allocated from 1, in the order they were added. The
+/// table can be moved to other handles with `relocate`, i.e. when combined with other providers
+/// in a `Composite`.
+///
+/// ```
+/// use drogue_microbit_gatt::{GattTable, Properties};
+/// use heapless::consts;
+/// use rubble::uuid::Uuid16;
+///
+/// let mut builder = GattTable::<consts::U8>::builder();
+/// builder.service(Uuid16(0x180F)).unwrap();
+/// let level = builder
+///     .characteristic(Uuid16(0x2A19), Properties::READ | Properties::NOTIFY, &[100])
+///     .unwrap();
+/// let mut table = builder.build();
+///
+/// table.set_value(level, &[99]).unwrap();
+/// ```
+pub struct GattTable<N: ArrayLength<Entry>> {
+    entries: Vec<Entry, N>,
+    first_handle: u16,
+}
+
+/// Builder for a `GattTable`.
+pub struct Builder<N: ArrayLength<Entry>> {
+    entries: Vec<Entry, N>,
+    service: Option<usize>,
+    characteristic: Option<usize>,
+}
+
+impl<N: ArrayLength<Entry>> Builder<N> {
+    /// Start a primary service. The following characteristics belong to this service.
+    pub fn service<U: Into<AttUuid>>(&mut self, uuid: U) -> Result<AttributeId, BuildError> {
+        self.add_service(PRIMARY_SERVICE_UUID.into(), uuid.into())
+    }
+
+    /// Start a secondary service. The following characteristics belong to this service.
+    pub fn secondary_service<U: Into<AttUuid>>(
+        &mut self,
+        uuid: U,
+    ) -> Result<AttributeId, BuildError> {
+        self.add_service(SECONDARY_SERVICE_UUID.into(), uuid.into())
+    }
+
+    /// Add a characteristic to the current service, returning the id of its value.
+    ///
+    /// A Client Characteristic Configuration descriptor is added if the characteristic supports
+    /// notifications or indications.
+    pub fn characteristic<U: Into<AttUuid>>(
+        &mut self,
+        uuid: U,
+        properties: Properties,
+        value: &[u8],
+    ) -> Result<AttributeId, BuildError> {
+        if self.service.is_none() {
+            return Err(BuildError::NoService);
+        }
+        let uuid = uuid.into();
+        let value = AttributeValue::from_slice(value).ok_or(BuildError::ValueTooLong)?;
+
+        let declaration = self.entries.len();
+        let value_handle = self.handle(declaration + 1).to_le_bytes();
+        let mut buf = [0; 19];
+        buf[..3].copy_from_slice(&[properties.bits(), value_handle[0], value_handle[1]]);
+        let len = encode_uuid(&uuid, &mut buf[3..]) + 3;
+        let decl = AttributeValue::from_slice(&buf[..len]).ok_or(BuildError::ValueTooLong)?;
+
+        let needed = if properties.is_configurable() { 3 } else { 2 };
+        if self.remaining() < needed {
+            return Err(BuildError::TableFull);
+        }
+        self.characteristic = None;
+        self.push(CHARACTERISTIC_UUID.into(), decl, Kind::Declaration)?;
+        self.characteristic = Some(declaration);
+        let id = self.push(uuid, value, Kind::Value(properties))?;
+        if properties.is_configurable() {
+            self.push(
+                CLIENT_CHARACTERISTIC_CONFIGURATION_UUID.into(),
+                AttributeValue::from_slice(&[0x00, 0x00]).unwrap(),
+                Kind::ClientConfig(properties),
+            )?;
+        }
+        Ok(id)
+    }
+
+    /// Add a descriptor to the current characteristic.
+    pub fn descriptor<U: Into<AttUuid>>(
+        &mut self,
+        uuid: U,
+        value: &[u8],
+        permissions: AttributeAccessPermissions,
+    ) -> Result<AttributeId, BuildError> {
+        if self.characteristic.is_none() {
+            return Err(BuildError::NoCharacteristic);
+        }
+        let value = AttributeValue::from_slice(value).ok_or(BuildError::ValueTooLong)?;
+        self.push(uuid.into(), value, Kind::Descriptor(permissions))
+    }
+
+    /// Number of attributes that can still be added.
+    pub fn remaining(&self) -> usize {
+        self.entries.capacity() - self.entries.len()
+    }
+
+    pub fn build(self) -> GattTable<N> {
+        GattTable {
+            entries: self.entries,
+            first_handle: FIRST_HANDLE,
+        }
+    }
+
+    fn add_service(&mut self, att_type: AttUuid, uuid: AttUuid) -> Result<AttributeId, BuildError> {
+        let mut buf = [0; 16];
+        let len = encode_uuid(&uuid, &mut buf);
+        let value = AttributeValue::from_slice(&buf[..len]).ok_or(BuildError::ValueTooLong)?;
+        self.service = None;
+        self.characteristic = None;
+        let id = self.push(att_type, value, Kind::Service)?;
+        self.service = Some(id.0);
+        Ok(id)
+    }
+
+    fn handle(&self, index: usize) -> u16 {
+        FIRST_HANDLE + index as u16
+    }
+
+    fn push(
+        &mut self,
+        att_type: AttUuid,
+        value: AttributeValue,
+        kind: Kind,
+    ) -> Result<AttributeId, BuildError> {
+        let index = self.entries.len();
+        let attribute = Attribute::new(att_type, Handle::from_raw(self.handle(index)), value);
+        self.entries
+            .push(Entry {
+                attribute,
+                kind,
+                group_end: index,
+            })
+            .map_err(|_| BuildError::TableFull)?;
+        // Extend the groups the attribute belongs to
+        for group in self.service.iter().chain(self.characteristic.iter()) {
+            self.entries[*group].group_end = index;
+        }
+        Ok(AttributeId(index))
+    }
+}
+
+const FIRST_HANDLE: u16 = 0x0001;
+
+fn encode_uuid(uuid: &AttUuid, buf: &mut [u8]) -> usize {
+    let space = buf.len();
+    let mut writer = ByteWriter::new(buf);
+    // The buffers are sized for 128-bit UUIDs
+    uuid.to_bytes(&mut writer).unwrap();
+    space - writer.space_left()
+}
+
+impl<N: ArrayLength<Entry>> GattTable<N> {
+    pub fn builder() -> Builder<N> {
+        Builder {
+            entries: Vec::new(),
+            service: None,
+            characteristic: None,
+        }
+    }
+
+    pub fn handle(&self, id: AttributeId) -> Handle {
+        self.entries[id.0].attribute.handle
+    }
+
+    /// The attribute with the given handle, if it belongs to this table.
+    pub fn id(&self, handle: Handle) -> Option<AttributeId> {
+        let index = usize::from(handle.as_u16().checked_sub(self.first_handle)?);
+        if index < self.entries.len() {
+            Some(AttributeId(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn value(&self, id: AttributeId) -> &[u8] {
+        self.entries[id.0].attribute.value.as_slice()
+    }
+
+    /// Update the value of an attribute.
+    pub fn set_value(&mut self, id: AttributeId, value: &[u8]) -> Result<(), Error> {
+        let value = AttributeValue::from_slice(value).ok_or(Error::InvalidLength)?;
+        self.entries[id.0].attribute.set_value(value);
+        Ok(())
+    }
+
+    /// The Client Characteristic Configuration of a characteristic value, if it has one.
+    pub fn client_config(&self, id: AttributeId) -> Option<AttributeId> {
+        match self.entries.get(id.0 + 1) {
+            Some(Entry {
+                kind: Kind::ClientConfig(_),
+                ..
+            }) => Some(AttributeId(id.0 + 1)),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the client has enabled notifications of a characteristic value.
+    pub fn notifications_enabled(&self, id: AttributeId) -> bool {
+        self.client_config_bits(id) & 0x0001 != 0
+    }
+
+    /// Returns true if the client has enabled indications of a characteristic value.
+    pub fn indications_enabled(&self, id: AttributeId) -> bool {
+        self.client_config_bits(id) & 0x0002 != 0
+    }
+
+    /// A notification of the current value of a characteristic.
+    pub fn notification(&self, id: AttributeId) -> Notification {
+        let attribute = &self.entries[id.0].attribute;
+        Notification {
+            handle: attribute.handle,
+            value: attribute.value,
+        }
+    }
+
+    /// The attributes in `range`, for delegating `for_attrs_in_range`.
+    pub fn attributes_in(
+        &self,
+        range: HandleRange,
+    ) -> impl Iterator<Item = &Attribute<AttributeValue>> {
+        self.entries
+            .iter()
+            .map(|e| &e.attribute)
+            .filter(move |a| range.contains(a.handle))
+    }
+
+    /// Move the attributes to consecutive handles starting at `first`, updating the value handles
+    /// in the characteristic declarations.
+    ///
+    /// Panics if `first` is 0, which is not a valid ATT handle, or if the table does not fit
+    /// below handle 0xFFFF.
+    pub fn relocate(&mut self, first: u16) {
+        assert!(first >= FIRST_HANDLE, "ATT handles start at 1");
+        assert!(
+            usize::from(u16::MAX - first) + 1 >= self.entries.len(),
+            "table does not fit in the handle space"
+        );
+        self.first_handle = first;
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            let handle = first + index as u16;
+            entry.attribute.handle = Handle::from_raw(handle);
+            if entry.kind == Kind::Declaration {
+                let mut value = [0; 19];
+                let len = entry.attribute.value.as_slice().len();
+                value[..len].copy_from_slice(entry.attribute.value.as_slice());
+                value[1..3].copy_from_slice(&(handle + 1).to_le_bytes());
+                entry.attribute.value = AttributeValue::from_slice(&value[..len]).unwrap();
+            }
+        }
+    }
+
+    /// The first handle of the table.
+    pub fn first_handle(&self) -> u16 {
+        self.first_handle
+    }
+
+    /// The last handle of the table, or one less than the first if the table is empty.
+    pub fn last_handle(&self) -> u16 {
+        // Cannot overflow, as the first handle is at least 1 and the table fits below 0xFFFF
+        self.first_handle - 1 + self.entries.len() as u16
+    }
+
+    fn client_config_bits(&self, id: AttributeId) -> u16 {
+        match self.client_config(id) {
+            Some(cccd) => {
+                let value = self.value(cccd);
+                u16::from_le_bytes([value[0], value[1]])
+            }
+            None => 0,
+        }
+    }
+}
+
+impl<N: ArrayLength<Entry>> AttributeProvider for GattTable<N> {
+    fn for_attrs_in_range(
+        &mut self,
+        range: HandleRange,
+        mut f: impl FnMut(&Self, &Attribute<dyn AttrValue>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let this = &*self;
+        for attr in this.attributes_in(range) {
+            f(this, attr)?;
+        }
+        Ok(())
+    }
+
+    fn is_grouping_attr(&self, uuid: AttUuid) -> bool {
+        uuid == PRIMARY_SERVICE_UUID || uuid == SECONDARY_SERVICE_UUID
+    }
+
+    fn group_end(&self, handle: Handle) -> Option<&Attribute<dyn AttrValue>> {
+        let entry = &self.entries[self.id(handle)?.0];
+        match entry.kind {
+            Kind::Service | Kind::Declaration => Some(&self.entries[entry.group_end].attribute),
+            _ => None,
+        }
+    }
+
+    fn attr_access_permissions(&self, handle: Handle) -> AttributeAccessPermissions {
+        let kind = match self.id(handle) {
+            Some(id) => self.entries[id.0].kind,
+            None => return AttributeAccessPermissions::Readable,
+        };
+        match kind {
+            Kind::Value(properties) if properties.is_writeable() => {
+                if properties.is_readable() {
+                    AttributeAccessPermissions::ReadableAndWriteable
+                } else {
+                    AttributeAccessPermissions::Writeable
+                }
+            }
+            Kind::ClientConfig(_) => AttributeAccessPermissions::ReadableAndWriteable,
+            Kind::Descriptor(permissions) => permissions,
+            _ => AttributeAccessPermissions::Readable,
+        }
+    }
+
+    fn write_attr(&mut self, handle: Handle, data: &[u8]) -> Result<(), Error> {
+        let id = self.id(handle).ok_or(Error::InvalidValue)?;
+        match self.entries[id.0].kind {
+            Kind::ClientConfig(properties) => {
+                if data.len() != 2 {
+                    return Err(Error::InvalidLength);
+                }
+                let mut allowed = 0;
+                if properties.contains(Properties::NOTIFY) {
+                    allowed |= 0x0001;
+                }
+                if properties.contains(Properties::INDICATE) {
+                    allowed |= 0x0002;
+                }
+                if u16::from_le_bytes([data[0], data[1]]) & !allowed != 0 {
+                    return Err(Error::InvalidValue);
+                }
+                self.set_value(id, data)
+            }
+            Kind::Value(properties) if properties.is_writeable() => self.set_value(id, data),
+            Kind::Descriptor(permissions) if permissions.is_writeable() => self.set_value(id, data),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heapless::consts;
+    use rubble::uuid::{Uuid128, Uuid16};
+
+    const CUSTOM_UUID: Uuid128 = Uuid128::from_bytes([
+        0xE9, 0x5D, 0xD9, 0x1D, 0x25, 0x1D, 0x47, 0x0A, 0xA0, 0x62, 0xFA, 0x19, 0x22, 0xDF, 0xA9,
+        0xA8,
+    ]);
+
+    struct Ids {
+        level: AttributeId,
+        name: AttributeId,
+        custom: AttributeId,
+    }
+
+    fn table() -> (GattTable<consts::U16>, Ids) {
+        let mut builder = GattTable::builder();
+        builder.service(Uuid16(0x180F)).unwrap(); // 1
+        let level = builder
+            .characteristic(
+                Uuid16(0x2A19),
+                Properties::READ | Properties::NOTIFY,
+                &[100],
+            )
+            .unwrap(); // 2, 3, 4
+        let name = builder
+            .descriptor(
+                Uuid16(0x2901),
+                b"Level",
+                AttributeAccessPermissions::ReadableAndWriteable,
+            )
+            .unwrap(); // 5
+        builder.service(CUSTOM_UUID).unwrap(); // 6
+        let custom = builder
+            .characteristic(CUSTOM_UUID, Properties::WRITE, &[])
+            .unwrap(); // 7, 8
+        (
+            builder.build(),
+            Ids {
+                level,
+                name,
+                custom,
+            },
+        )
+    }
+
+    fn handles(table: &mut GattTable<consts::U16>, start: u16, end: u16) -> Vec<u16, consts::U16> {
+        let mut handles = Vec::new();
+        table
+            .for_attrs_in_range(
+                HandleRange::new(Handle::from_raw(start), Handle::from_raw(end)),
+                |_, attr| {
+                    handles.push(attr.handle.as_u16()).unwrap();
+                    Ok(())
+                },
+            )
+            .unwrap();
+        handles
+    }
+
+    #[test]
+    fn declarations() {
+        let (table, ids) = table();
+        assert_eq!(table.handle(ids.level), Handle::from_raw(3));
+        assert_eq!(table.handle(ids.name), Handle::from_raw(5));
+        assert_eq!(table.handle(ids.custom), Handle::from_raw(8));
+
+        let value = |handle| table.value(table.id(Handle::from_raw(handle)).unwrap());
+        assert_eq!(value(1), &[0x0F, 0x18]);
+        assert_eq!(value(2), &[0x12, 0x03, 0x00, 0x19, 0x2A]);
+        assert_eq!(value(4), &[0x00, 0x00]);
+        assert_eq!(value(6)[..2], [0xA8, 0xA9]);
+        assert_eq!(value(7)[..5], [0x08, 0x08, 0x00, 0xA8, 0xA9]);
+        assert_eq!(value(7).len(), 19);
+    }
+
+    #[test]
+    fn ranges() {
+        let (mut table, _) = table();
+        assert_eq!(&handles(&mut table, 0, 3)[..], &[1, 2, 3]);
+        assert_eq!(&handles(&mut table, 5, 0xFFFF)[..], &[5, 6, 7, 8]);
+        assert_eq!(&handles(&mut table, 9, 0xFFFF)[..], &[]);
+        assert_eq!(table.id(Handle::from_raw(0)), None);
+    }
+
+    #[test]
+    fn groups() {
+        let (table, _) = table();
+        let end = |handle| {
+            table
+                .group_end(Handle::from_raw(handle))
+                .map(|a| a.handle.as_u16())
+        };
+        assert_eq!(end(1), Some(5));
+        assert_eq!(end(2), Some(5));
+        assert_eq!(end(3), None);
+        assert_eq!(end(6), Some(8));
+        assert_eq!(end(7), Some(8));
+        assert_eq!(end(9), None);
+
+        assert!(table.is_grouping_attr(PRIMARY_SERVICE_UUID.into()));
+        assert!(table.is_grouping_attr(SECONDARY_SERVICE_UUID.into()));
+        assert!(!table.is_grouping_attr(Uuid16(0x2A19).into()));
+    }
+
+    #[test]
+    fn characteristics_are_not_groups() {
+        // Read By Group Type on a characteristic declaration must be answered with Unsupported
+        // Group Type, which the server does for any type that is not a grouping attribute.
+        let (table, _) = table();
+        assert!(!table.is_grouping_attr(CHARACTERISTIC_UUID.into()));
+    }
+
+    #[test]
+    fn consecutive_characteristics() {
+        let mut builder = GattTable::<consts::U8>::builder();
+        builder.service(Uuid16(0x180A)).unwrap(); // 1
+        builder
+            .characteristic(Uuid16(0x2A29), Properties::READ, b"Drogue")
+            .unwrap(); // 2, 3
+        builder
+            .characteristic(Uuid16(0x2A24), Properties::READ, b"micro:bit")
+            .unwrap(); // 4, 5
+        let table = builder.build();
+        let end = |handle| {
+            table
+                .group_end(Handle::from_raw(handle))
+                .map(|a| a.handle.as_u16())
+        };
+        assert_eq!(end(1), Some(5));
+        assert_eq!(end(2), Some(3));
+        assert_eq!(end(4), Some(5));
+    }
+
+    #[test]
+    fn writes() {
+        let (mut table, ids) = table();
+        let cccd = table.handle(table.client_config(ids.level).unwrap());
+        assert_eq!(table.write_attr(cccd, &[0x01]), Err(Error::InvalidLength));
+        assert_eq!(
+            table.write_attr(cccd, &[0x02, 0x00]),
+            Err(Error::InvalidValue)
+        );
+        table.write_attr(cccd, &[0x01, 0x00]).unwrap();
+        assert!(table.notifications_enabled(ids.level));
+        assert!(!table.indications_enabled(ids.level));
+
+        let level = table.handle(ids.level);
+        assert_eq!(table.write_attr(level, &[50]), Err(Error::InvalidValue));
+        assert_eq!(
+            table.attr_access_permissions(level),
+            AttributeAccessPermissions::Readable
+        );
+
+        let custom = table.handle(ids.custom);
+        assert_eq!(
+            table.attr_access_permissions(custom),
+            AttributeAccessPermissions::Writeable
+        );
+        table.write_attr(custom, b"hello").unwrap();
+        assert_eq!(table.value(ids.custom), b"hello");
+        assert_eq!(table.client_config(ids.custom), None);
+
+        let name = table.handle(ids.name);
+        table.write_attr(name, b"Battery").unwrap();
+        assert_eq!(table.value(ids.name), b"Battery");
+        assert_eq!(table.write_attr(name, &[0; 21]), Err(Error::InvalidLength));
+    }
+
+    #[test]
+    fn build_errors() {
+        let mut builder = GattTable::<consts::U3>::builder();
+        assert_eq!(
+            builder
+                .characteristic(Uuid16(0x2A19), Properties::READ, &[])
+                .err(),
+            Some(BuildError::NoService)
+        );
+        builder.service(Uuid16(0x180F)).unwrap();
+        assert_eq!(
+            builder
+                .descriptor(Uuid16(0x2901), &[], AttributeAccessPermissions::Readable)
+                .err(),
+            Some(BuildError::NoCharacteristic)
+        );
+        assert_eq!(
+            builder
+                .characteristic(Uuid16(0x2A19), Properties::READ, &[0; 21])
+                .err(),
+            Some(BuildError::ValueTooLong)
+        );
+        assert_eq!(
+            builder
+                .characteristic(Uuid16(0x2A19), Properties::NOTIFY, &[])
+                .err(),
+            Some(BuildError::TableFull)
+        );
+        builder
+            .characteristic(Uuid16(0x2A19), Properties::READ, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn notification() {
+        let (mut table, ids) = table();
+        table.set_value(ids.level, &[42]).unwrap();
+        let notification = table.notification(ids.level);
+        assert_eq!(notification.handle(), Handle::from_raw(3));
+        assert_eq!(notification.value(), &[42]);
+    }
+
+    #[test]
+    fn empty_table_handles() {
+        let mut table = GattTable::<consts::U4>::builder().build();
+        assert_eq!(table.first_handle(), 1);
+        assert_eq!(table.last_handle(), 0);
+        table.relocate(0xFFFF);
+        assert_eq!(table.last_handle(), 0xFFFE);
+        assert_eq!(table.id(Handle::from_raw(0xFFFF)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ATT handles start at 1")]
+    fn relocate_rejects_handle_zero() {
+        let (mut table, _) = table();
+        table.relocate(0);
+    }
+}
@@ -0,0 +1,32 @@
+use rubble::att::AttrValue;
+
+/// Largest attribute value, which fits in a notification with the default ATT MTU of 23 bytes.
+pub const MAX_VALUE_LEN: usize = 20;
+
+/// An attribute value stored inline in the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributeValue {
+    data: [u8; MAX_VALUE_LEN],
+    len: usize,
+}
+
+impl AttributeValue {
+    /// Create a value from `data`, or `None` if it is longer than `MAX_VALUE_LEN`.
+    pub fn from_slice(data: &[u8]) -> Option<Self> {
+        if data.len() > MAX_VALUE_LEN {
+            return None;
+        }
+        let mut value = AttributeValue {
+            data: [0; MAX_VALUE_LEN],
+            len: data.len(),
+        };
+        value.data[..data.len()].copy_from_slice(data);
+        Some(value)
+    }
+}
+
+impl AttrValue for AttributeValue {
+    fn as_slice(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
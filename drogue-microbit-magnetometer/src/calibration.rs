@@ -0,0 +1,123 @@
+/// Hard-iron calibration of the magnetometer.
+///
+/// Magnetised parts of the board add a constant offset to the measured field, which moves the
+/// sphere traced by the field when the board is rotated away from the origin. The calibration
+/// is the centre of that sphere, and its radius is the strength of the earth's field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Calibration {
+    /// Centre of the sphere in units of 0.1 µT.
+    pub centre: [i16; 3],
+    /// Radius of the sphere in units of 0.1 µT.
+    pub radius: u16,
+}
+
+impl Calibration {
+    /// Remove the hard-iron offset from a sample.
+    pub fn apply(&self, field: [i16; 3]) -> [i16; 3] {
+        let mut calibrated = [0; 3];
+        for (i, value) in calibrated.iter_mut().enumerate() {
+            *value = field[i].saturating_sub(self.centre[i]);
+        }
+        calibrated
+    }
+}
+
+/// Samples collected for calibration, fitting a sphere to the minimum and maximum on each axis.
+///
+/// For a good fit the board must be rotated so that every axis points both towards and away from
+/// the north, which is what the `Calibrator` guides the user through.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CalibrationData {
+    min: [i16; 3],
+    max: [i16; 3],
+    samples: u32,
+}
+
+impl CalibrationData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, field: [i16; 3]) {
+        let first = self.samples == 0;
+        for (value, (min, max)) in field
+            .iter()
+            .zip(self.min.iter_mut().zip(self.max.iter_mut()))
+        {
+            if first || value < min {
+                *min = *value;
+            }
+            if first || value > max {
+                *max = *value;
+            }
+        }
+        self.samples = self.samples.saturating_add(1);
+    }
+
+    /// The number of samples added.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// The sphere through the extremes of the samples, or `None` if there are no samples.
+    pub fn calibration(&self) -> Option<Calibration> {
+        if self.samples == 0 {
+            return None;
+        }
+        let mut calibration = Calibration::default();
+        let mut radius = 0;
+        for i in 0..3 {
+            let (min, max) = (i32::from(self.min[i]), i32::from(self.max[i]));
+            calibration.centre[i] = ((min + max) / 2) as i16;
+            radius += (max - min) / 2;
+        }
+        calibration.radius = (radius / 3) as u16;
+        Some(calibration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sphere() {
+        let mut data = CalibrationData::new();
+        assert_eq!(data.calibration(), None);
+
+        // A field of 500 rotated around a centre of (100, -200, 300)
+        for field in [
+            [600, -200, 300],
+            [-400, -200, 300],
+            [100, 300, 300],
+            [100, -700, 300],
+            [100, -200, 800],
+            [100, -200, -200],
+            [400, 100, 300],
+        ]
+        .iter()
+        {
+            data.add(*field);
+        }
+        assert_eq!(data.samples(), 7);
+
+        let calibration = data.calibration().unwrap();
+        assert_eq!(calibration.centre, [100, -200, 300]);
+        assert_eq!(calibration.radius, 500);
+        assert_eq!(calibration.apply([600, -200, 300]), [500, 0, 0]);
+        assert_eq!(calibration.apply([-32768, 0, 0]), [-32768, 200, -300]);
+    }
+
+    #[test]
+    fn single_sample() {
+        let mut data = CalibrationData::new();
+        data.add([-50, 20, 7]);
+        assert_eq!(
+            data.calibration(),
+            Some(Calibration {
+                centre: [-50, 20, 7],
+                radius: 0,
+            })
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use core::fmt::Debug;
+
+use drogue_microbit_accelerometer::Acceleration;
+use drogue_microbit_matrix::{Image, LedMatrix, MAX_BRIGHTNESS};
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::{Calibration, CalibrationData};
+
+/// Acceleration in milli-g that moves the cursor one pixel away from the centre.
+const PIXEL1_THRESHOLD: i16 = 200;
+/// Acceleration in milli-g that moves the cursor to the edge.
+const PIXEL2_THRESHOLD: i16 = 680;
+/// Number of updates between toggling the cursor.
+const BLINK_PERIOD: u8 = 5;
+
+/// Guides the user through calibrating the compass with the "fill the screen" game of the micro:bit
+/// runtime.
+///
+/// Tilting the board moves a blinking cursor around the display, lighting every pixel it visits.
+/// Lighting all 25 pixels turns every axis of the magnetometer in all directions, and the samples
+/// collected meanwhile are used for the calibration.
+///
+/// `update` should be called at around 50 Hz, and the display updated with `show` after each call.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Calibrator {
+    data: CalibrationData,
+    visited: u32,
+    cursor: (usize, usize),
+    ticks: u8,
+}
+
+impl Calibrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sample of `Mma8653::accel` and `Mag3110::field_raw`, returning the calibration when
+    /// the last pixel is lit.
+    pub fn update(&mut self, accel: Acceleration, field: [i16; 3]) -> Option<Calibration> {
+        if self.is_complete() {
+            return None;
+        }
+        self.data.add(field);
+        self.ticks = (self.ticks + 1) % (2 * BLINK_PERIOD);
+
+        // The cursor rolls like a marble, towards the edge that is tilted down
+        self.cursor = (pixel(accel.x), pixel(accel.y));
+        self.visited |= 1 << (self.cursor.1 * 5 + self.cursor.0);
+        if self.is_complete() {
+            self.data.calibration()
+        } else {
+            None
+        }
+    }
+
+    /// Returns true when all pixels have been lit.
+    pub fn is_complete(&self) -> bool {
+        self.visited == (1 << 25) - 1
+    }
+
+    /// The display of the game, with the visited pixels lit and a blinking cursor.
+    pub fn image(&self) -> Image {
+        let mut image = Image::blank();
+        for y in 0..5 {
+            for x in 0..5 {
+                if self.visited & 1 << (y * 5 + x) != 0 {
+                    image.set_pixel(x, y, MAX_BRIGHTNESS);
+                }
+            }
+        }
+        if !self.is_complete() {
+            let on = self.ticks < BLINK_PERIOD;
+            let (x, y) = self.cursor;
+            image.set_pixel(x, y, if on { MAX_BRIGHTNESS } else { 0 });
+        }
+        image
+    }
+
+    /// Show the game on the LED matrix.
+    pub fn show<P>(&self, led: &mut LedMatrix<P>)
+    where
+        P: OutputPin,
+        P::Error: Debug,
+    {
+        led.show(&self.image());
+    }
+}
+
+fn pixel(accel: i16) -> usize {
+    if accel < -PIXEL2_THRESHOLD {
+        0
+    } else if accel < -PIXEL1_THRESHOLD {
+        1
+    } else if accel > PIXEL2_THRESHOLD {
+        4
+    } else if accel > PIXEL1_THRESHOLD {
+        3
+    } else {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POSITIONS: [i16; 5] = [-700, -400, 0, 400, 700];
+
+    #[test]
+    fn fill_the_screen() {
+        let mut calibrator = Calibrator::new();
+        let flat = Acceleration::new(0, 0, -1000);
+        assert_eq!(calibrator.update(flat, [0, 0, 0]), None);
+        assert_eq!(calibrator.image().get_pixel(2, 2), MAX_BRIGHTNESS);
+        assert_eq!(calibrator.image().get_pixel(0, 0), 0);
+
+        let mut result = None;
+        for (i, y) in POSITIONS.iter().enumerate() {
+            for (j, x) in POSITIONS.iter().enumerate() {
+                assert!(!calibrator.is_complete());
+                let field = [100 + 10 * i as i16, -100 - 10 * j as i16, 50];
+                result = calibrator.update(Acceleration::new(*x, *y, -700), field);
+            }
+        }
+        assert!(calibrator.is_complete());
+        assert_eq!(calibrator.image(), Image::new([[MAX_BRIGHTNESS; 5]; 5]));
+        assert_eq!(
+            result,
+            Some(Calibration {
+                centre: [70, -70, 25],
+                radius: 55,
+            })
+        );
+        assert_eq!(calibrator.update(flat, [0, 0, 0]), None);
+    }
+
+    #[test]
+    fn cursor_blinks() {
+        let mut calibrator = Calibrator::new();
+        let left = Acceleration::new(-1000, 0, 0);
+        let mut brightness = [0; 2 * BLINK_PERIOD as usize];
+        for b in brightness.iter_mut() {
+            calibrator.update(left, [0, 0, 0]);
+            *b = calibrator.image().get_pixel(0, 2);
+        }
+        assert_eq!(brightness[..4], [MAX_BRIGHTNESS; 4]);
+        assert_eq!(brightness[4..9], [0; 5]);
+        assert_eq!(brightness[9], MAX_BRIGHTNESS);
+    }
+}
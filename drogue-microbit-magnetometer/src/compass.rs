@@ -0,0 +1,136 @@
+use core::f32::consts::PI;
+
+use drogue_microbit_accelerometer::Acceleration;
+use libm::{atan2f, atanf, cosf, sinf};
+
+use crate::Calibration;
+
+/// Tilt-compensated heading in degrees clockwise from magnetic north, between 0 and 360.
+///
+/// Both the magnetic field and the gravity are in the north-east-down frame of the board, where x
+/// points towards the logo, y towards the right edge and z out of the back when the display faces
+/// up. Gravity is positive along z when the board lies flat. The tilt is only compensated for
+/// pitch angles between -90 and 90 degrees.
+pub fn heading(field: [f32; 3], gravity: [f32; 3]) -> f32 {
+    let roll = atan2f(gravity[1], gravity[2]);
+    let (sin_roll, cos_roll) = (sinf(roll), cosf(roll));
+    let pitch = atanf(-gravity[0] / (gravity[1] * sin_roll + gravity[2] * cos_roll));
+    let (sin_pitch, cos_pitch) = (sinf(pitch), cosf(pitch));
+
+    // Rotate the field back to the horizontal plane
+    let north =
+        field[0] * cos_pitch + field[1] * sin_pitch * sin_roll + field[2] * sin_pitch * cos_roll;
+    let west = field[2] * sin_roll - field[1] * cos_roll;
+
+    let degrees = atan2f(west, north) * 180.0 / PI;
+    if degrees < 0.0 {
+        degrees + 360.0
+    } else {
+        degrees
+    }
+}
+
+/// Compass for the micro:bit v1, combining calibrated samples of the MAG3110 with samples of the
+/// MMA8653 to compute the heading of the board.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Compass {
+    calibration: Calibration,
+}
+
+impl Compass {
+    pub fn new(calibration: Calibration) -> Self {
+        Self { calibration }
+    }
+
+    pub fn calibration(&self) -> &Calibration {
+        &self.calibration
+    }
+
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = calibration;
+    }
+
+    /// The heading of the board in degrees, from a sample of `Mag3110::field_raw` and a sample of
+    /// `Mma8653::accel`.
+    pub fn heading(&self, field: [i16; 3], accel: Acceleration) -> f32 {
+        let field = self.calibration.apply(field);
+        // Map the frames of the sensors to the north-east-down frame of the board
+        heading(
+            [
+                -f32::from(field[1]),
+                f32::from(field[0]),
+                -f32::from(field[2]),
+            ],
+            [-f32::from(accel.y), f32::from(accel.x), -f32::from(accel.z)],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAD: f32 = PI / 180.0;
+
+    /// Rotate a vector from the earth frame to the frame of a board with the given yaw, pitch and
+    /// roll in degrees.
+    fn to_board(v: [f32; 3], yaw: f32, pitch: f32, roll: f32) -> [f32; 3] {
+        let (s, c) = (sinf(-yaw * RAD), cosf(-yaw * RAD));
+        let v = [v[0] * c - v[1] * s, v[0] * s + v[1] * c, v[2]];
+        let (s, c) = (sinf(-pitch * RAD), cosf(-pitch * RAD));
+        let v = [v[0] * c + v[2] * s, v[1], -v[0] * s + v[2] * c];
+        let (s, c) = (sinf(-roll * RAD), cosf(-roll * RAD));
+        [v[0], v[1] * c - v[2] * s, v[1] * s + v[2] * c]
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        let error = (actual - expected + 540.0) % 360.0 - 180.0;
+        assert!(error.abs() < 0.5, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn flat() {
+        // The earth's field dips down towards the north
+        let field = [200.0, 0.0, 450.0];
+        for yaw in [0.0, 45.0, 90.0, 135.0, 180.0, 270.0, 359.0].iter() {
+            let board_field = to_board(field, *yaw, 0.0, 0.0);
+            assert_close(heading(board_field, [0.0, 0.0, 1000.0]), *yaw);
+        }
+    }
+
+    #[test]
+    fn tilted() {
+        let field = [200.0, 0.0, 450.0];
+        for yaw in [10.0, 100.0, 200.0, 300.0].iter() {
+            for &(pitch, roll) in [(30.0, 0.0), (-45.0, 20.0), (10.0, -60.0), (60.0, 150.0)].iter()
+            {
+                let board_field = to_board(field, *yaw, pitch, roll);
+                let gravity = to_board([0.0, 0.0, 1000.0], *yaw, pitch, roll);
+                assert_close(heading(board_field, gravity), *yaw);
+            }
+        }
+    }
+
+    #[test]
+    fn sensor_frames() {
+        let compass = Compass::new(Calibration {
+            centre: [100, 100, 0],
+            radius: 500,
+        });
+        let flat = Acceleration::new(0, 0, -1000);
+
+        // Facing north, the field along the sensor's -y axis
+        assert_close(compass.heading([100, -100, -450], flat), 0.0);
+        // Facing east, the north is to the left
+        assert_close(compass.heading([-100, 100, -450], flat), 90.0);
+        // Tilted to the right
+        let tilted = Acceleration::new(500, 0, -866);
+        let field = to_board([200.0, 0.0, 450.0], 0.0, 0.0, 30.0);
+        let raw = [
+            field[1] as i16 + 100,
+            -field[0] as i16 + 100,
+            -field[2] as i16,
+        ];
+        assert_close(compass.heading(raw, tilted), 0.0);
+    }
+}
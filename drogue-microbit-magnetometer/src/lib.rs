@@ -0,0 +1,13 @@
+//! Driver for the MAG3110 magnetometer of the micro:bit v1, with hard-iron calibration and a
+//! tilt-compensated compass heading.
+#![no_std]
+
+mod calibration;
+mod calibrator;
+mod compass;
+mod mag3110;
+
+pub use calibration::{Calibration, CalibrationData};
+pub use calibrator::Calibrator;
+pub use compass::{heading, Compass};
+pub use mag3110::{DataRate, Error, Mag3110, ADDRESS};
@@ -0,0 +1,175 @@
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+/// The I2C address of the MAG3110 on the micro:bit.
+pub const ADDRESS: u8 = 0x0E;
+
+/// Value of the WHO_AM_I register of the MAG3110.
+const DEVICE_ID: u8 = 0xC4;
+
+const DR_STATUS: u8 = 0x00;
+const OUT_X_MSB: u8 = 0x01;
+const WHO_AM_I: u8 = 0x07;
+const DIE_TEMP: u8 = 0x0F;
+const CTRL_REG1: u8 = 0x10;
+const CTRL_REG2: u8 = 0x11;
+
+const DR_STATUS_ZYXDR: u8 = 1 << 3;
+const CTRL_REG1_AC: u8 = 1 << 0;
+const CTRL_REG2_AUTO_MRST_EN: u8 = 1 << 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// Communication with the device failed.
+    I2c(E),
+    /// The WHO_AM_I register did not identify a MAG3110.
+    UnknownDevice(u8),
+}
+
+/// Output data rate, with 16 times oversampling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataRate {
+    Hz80 = 0,
+    Hz40 = 1,
+    Hz20 = 2,
+    Hz10 = 3,
+    Hz5 = 4,
+    Hz2_5 = 5,
+    Hz1_25 = 6,
+    Hz0_63 = 7,
+}
+
+/// Driver for the MAG3110 3-axis magnetometer of the micro:bit v1.
+///
+/// Samples are in the frame of the sensor, with a resolution of 0.1 µT. They include the hard-iron
+/// offset of the board, which is removed with a `Calibration`.
+pub struct Mag3110<I2C> {
+    i2c: I2C,
+    ctrl_reg1: u8,
+}
+
+impl<I2C, E> Mag3110<I2C>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Verify the device and start sampling at 10 Hz.
+    pub fn new(i2c: I2C) -> Result<Self, Error<E>> {
+        let mut mag = Self { i2c, ctrl_reg1: 0 };
+        let id = mag.read_register(WHO_AM_I)?;
+        if id != DEVICE_ID {
+            return Err(Error::UnknownDevice(id));
+        }
+        mag.write_register(CTRL_REG1, 0)?;
+        // Reset the sensor before each measurement, to recover from strong fields
+        mag.write_register(CTRL_REG2, CTRL_REG2_AUTO_MRST_EN)?;
+        mag.ctrl_reg1 = (DataRate::Hz10 as u8) << 5 | CTRL_REG1_AC;
+        mag.write_register(CTRL_REG1, mag.ctrl_reg1)?;
+        Ok(mag)
+    }
+
+    pub fn set_data_rate(&mut self, rate: DataRate) -> Result<(), Error<E>> {
+        // The rate can only be changed in standby
+        self.write_register(CTRL_REG1, self.ctrl_reg1 & !CTRL_REG1_AC)?;
+        self.ctrl_reg1 = (self.ctrl_reg1 & !(0b111 << 5)) | (rate as u8) << 5;
+        self.write_register(CTRL_REG1, self.ctrl_reg1)
+    }
+
+    /// Returns true if a new sample is available on all axes.
+    pub fn is_data_ready(&mut self) -> Result<bool, Error<E>> {
+        Ok(self.read_register(DR_STATUS)? & DR_STATUS_ZYXDR != 0)
+    }
+
+    /// The latest sample of each axis in units of 0.1 µT.
+    pub fn field_raw(&mut self) -> Result<[i16; 3], Error<E>> {
+        let mut data = [0; 6];
+        self.i2c
+            .write_read(ADDRESS, &[OUT_X_MSB], &mut data)
+            .map_err(Error::I2c)?;
+        let axis = |i: usize| i16::from_be_bytes([data[i], data[i + 1]]);
+        Ok([axis(0), axis(2), axis(4)])
+    }
+
+    /// The die temperature in degrees Celsius. It is not factory calibrated, so it is only useful
+    /// for relative measurements.
+    pub fn die_temperature(&mut self) -> Result<i8, Error<E>> {
+        Ok(self.read_register(DIE_TEMP)? as i8)
+    }
+
+    pub fn free(self) -> I2C {
+        self.i2c
+    }
+
+    fn read_register(&mut self, register: u8) -> Result<u8, Error<E>> {
+        let mut value = [0];
+        self.i2c
+            .write_read(ADDRESS, &[register], &mut value)
+            .map_err(Error::I2c)?;
+        Ok(value[0])
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) -> Result<(), Error<E>> {
+        self.i2c
+            .write(ADDRESS, &[register, value])
+            .map_err(Error::I2c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drogue_microbit_accelerometer::mock::MockI2c;
+
+    fn device() -> MockI2c {
+        let mut i2c = MockI2c::new(ADDRESS);
+        i2c.registers[WHO_AM_I as usize] = DEVICE_ID;
+        i2c
+    }
+
+    #[test]
+    fn init() {
+        let mag = Mag3110::new(device()).unwrap();
+        assert_eq!(
+            mag.free().writes,
+            [(CTRL_REG1, 0x00), (CTRL_REG2, 0x80), (CTRL_REG1, 0x61)]
+        );
+
+        let mut i2c = device();
+        i2c.registers[WHO_AM_I as usize] = 0x5A;
+        assert_eq!(
+            Mag3110::new(i2c)
+                .err()
+                .map(|e| e == Error::UnknownDevice(0x5A)),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn data_rate() {
+        let mut mag = Mag3110::new(device()).unwrap();
+        mag.i2c.writes.clear();
+        mag.set_data_rate(DataRate::Hz80).unwrap();
+        mag.set_data_rate(DataRate::Hz0_63).unwrap();
+        assert_eq!(
+            mag.i2c.writes,
+            [
+                (CTRL_REG1, 0x60),
+                (CTRL_REG1, 0x01),
+                (CTRL_REG1, 0x00),
+                (CTRL_REG1, 0xE1)
+            ]
+        );
+    }
+
+    #[test]
+    fn samples() {
+        let mut mag = Mag3110::new(device()).unwrap();
+        mag.i2c.registers[1..7].copy_from_slice(&[0x01, 0x2C, 0xFE, 0x0C, 0x80, 0x00]);
+        assert_eq!(mag.field_raw().unwrap(), [300, -500, -32768]);
+
+        assert!(!mag.is_data_ready().unwrap());
+        mag.i2c.registers[DR_STATUS as usize] = 0x08;
+        assert!(mag.is_data_ready().unwrap());
+
+        mag.i2c.registers[DIE_TEMP as usize] = 0xFB;
+        assert_eq!(mag.die_temperature().unwrap(), -5);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::Image;
+
+/// A single frame of an animation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub image: Image,
+    /// Number of ticks the frame is shown for.
+    pub duration: u32,
+}
+
+impl Frame {
+    pub const fn new(image: Image, duration: u32) -> Frame {
+        Frame { image, duration }
+    }
+}
+
+/// What to do when an animation reaches its last frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationMode {
+    /// Stop at the last frame.
+    Once,
+    /// Start over from the first frame.
+    Loop,
+    /// Play the frames backwards, then forwards again, and so on.
+    PingPong,
+}
+
+/// A sequence of frames.
+#[derive(Clone, Copy, Debug)]
+pub struct Animation<'a> {
+    frames: &'a [Frame],
+    mode: AnimationMode,
+}
+
+impl<'a> Animation<'a> {
+    pub const fn new(frames: &'a [Frame], mode: AnimationMode) -> Animation<'a> {
+        Animation { frames, mode }
+    }
+}
+
+/// Plays an `Animation` one tick at a time.
+///
+/// The player does not block, and is meant to be driven from a periodic timer interrupt, where the
+/// frame durations are counted in interrupts:
+///
+/// ```ignore
+/// if let Some(image) = player.tick() {
+///     led.show(image);
+/// }
+/// ```
+pub struct AnimationPlayer<'a> {
+    animation: Animation<'a>,
+    index: usize,
+    ticks: u32,
+    backwards: bool,
+    finished: bool,
+}
+
+impl<'a> AnimationPlayer<'a> {
+    pub fn new(animation: Animation<'a>) -> AnimationPlayer<'a> {
+        AnimationPlayer {
+            animation,
+            index: 0,
+            ticks: 0,
+            backwards: false,
+            finished: animation.frames.is_empty(),
+        }
+    }
+
+    /// The image of the current frame, or `None` if the animation has no frames.
+    pub fn current(&self) -> Option<&Image> {
+        self.animation.frames.get(self.index).map(|f| &f.image)
+    }
+
+    /// Returns true if a `AnimationMode::Once` animation has played its last frame.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Start the animation over from the first frame.
+    pub fn reset(&mut self) {
+        *self = AnimationPlayer::new(self.animation);
+    }
+
+    /// Count one tick. Returns the image of the next frame if the animation moved on to it.
+    pub fn tick(&mut self) -> Option<&Image> {
+        if self.finished {
+            return None;
+        }
+        self.ticks += 1;
+        if self.ticks < self.animation.frames[self.index].duration {
+            return None;
+        }
+        self.ticks = 0;
+
+        let previous = self.index;
+        self.index = self.next_index();
+        if self.index == previous {
+            // Single frame or end of a one-shot animation, so nothing changes
+            if self.animation.mode == AnimationMode::Once {
+                self.finished = true;
+            }
+            return None;
+        }
+        self.current()
+    }
+
+    fn next_index(&mut self) -> usize {
+        let last = self.animation.frames.len() - 1;
+        match self.animation.mode {
+            AnimationMode::Once => core::cmp::min(self.index + 1, last),
+            AnimationMode::Loop => {
+                if self.index == last {
+                    0
+                } else {
+                    self.index + 1
+                }
+            }
+            AnimationMode::PingPong => {
+                if last == 0 {
+                    return 0;
+                }
+                if self.index == last {
+                    self.backwards = true;
+                } else if self.index == 0 {
+                    self.backwards = false;
+                }
+                if self.backwards {
+                    self.index - 1
+                } else {
+                    self.index + 1
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAMES: [Frame; 3] = [
+        Frame::new(Image::CLOCK12, 1),
+        Frame::new(Image::CLOCK3, 2),
+        Frame::new(Image::CLOCK6, 1),
+    ];
+
+    /// Tick `n` times, returning the images that were switched to.
+    fn play(player: &mut AnimationPlayer, n: usize) -> [Option<Image>; 8] {
+        let mut shown = [None; 8];
+        for s in shown.iter_mut().take(n) {
+            *s = player.tick().copied();
+        }
+        shown
+    }
+
+    #[test]
+    fn frame_durations() {
+        let mut player = AnimationPlayer::new(Animation::new(&FRAMES, AnimationMode::Loop));
+        assert_eq!(player.current(), Some(&Image::CLOCK12));
+        assert_eq!(
+            play(&mut player, 6),
+            [
+                Some(Image::CLOCK3),
+                None,
+                Some(Image::CLOCK6),
+                Some(Image::CLOCK12),
+                Some(Image::CLOCK3),
+                None,
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn play_once() {
+        let mut player = AnimationPlayer::new(Animation::new(&FRAMES, AnimationMode::Once));
+        assert_eq!(
+            play(&mut player, 5),
+            [
+                Some(Image::CLOCK3),
+                None,
+                Some(Image::CLOCK6),
+                None,
+                None,
+                None,
+                None,
+                None
+            ]
+        );
+        assert!(player.is_finished());
+        assert_eq!(player.current(), Some(&Image::CLOCK6));
+
+        player.reset();
+        assert!(!player.is_finished());
+        assert_eq!(player.current(), Some(&Image::CLOCK12));
+    }
+
+    #[test]
+    fn ping_pong() {
+        let mut player = AnimationPlayer::new(Animation::new(&FRAMES, AnimationMode::PingPong));
+        assert_eq!(
+            play(&mut player, 8),
+            [
+                Some(Image::CLOCK3),
+                None,
+                Some(Image::CLOCK6),
+                Some(Image::CLOCK3),
+                None,
+                Some(Image::CLOCK12),
+                Some(Image::CLOCK3),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn single_and_empty_animations() {
+        let single = [Frame::new(Image::HEART, 1)];
+        let mut player = AnimationPlayer::new(Animation::new(&single, AnimationMode::PingPong));
+        assert_eq!(play(&mut player, 3), [None; 8]);
+        assert_eq!(player.current(), Some(&Image::HEART));
+
+        let mut player = AnimationPlayer::new(Animation::new(&[], AnimationMode::Loop));
+        assert!(player.is_finished());
+        assert_eq!(player.current(), None);
+        assert_eq!(player.tick(), None);
+    }
+}
@@ -0,0 +1,158 @@
+//! A 5x5 bitmap font covering the printable ASCII characters.
+
+use crate::{Image, MAX_BRIGHTNESS};
+
+/// The first character in the font.
+const FIRST: u8 = b' ';
+
+/// The last character in the font.
+const LAST: u8 = b'~';
+
+/// Glyphs for the characters between `FIRST` and `LAST`. Each glyph is 5 rows, where bit 4 is the
+/// leftmost column.
+#[rustfmt::skip]
+const GLYPHS: [[u8; 5]; (LAST - FIRST + 1) as usize] = [
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000], // ' '
+    [0b00100, 0b00100, 0b00100, 0b00000, 0b00100], // '!'
+    [0b01010, 0b01010, 0b00000, 0b00000, 0b00000], // '"'
+    [0b01010, 0b11111, 0b01010, 0b11111, 0b01010], // '#'
+    [0b01111, 0b10100, 0b01110, 0b00101, 0b11110], // '$'
+    [0b11001, 0b11010, 0b00100, 0b01011, 0b10011], // '%'
+    [0b01100, 0b10010, 0b01100, 0b10010, 0b01101], // '&'
+    [0b00100, 0b00100, 0b00000, 0b00000, 0b00000], // '\''
+    [0b00010, 0b00100, 0b00100, 0b00100, 0b00010], // '('
+    [0b01000, 0b00100, 0b00100, 0b00100, 0b01000], // ')'
+    [0b00000, 0b01010, 0b00100, 0b01010, 0b00000], // '*'
+    [0b00000, 0b00100, 0b01110, 0b00100, 0b00000], // '+'
+    [0b00000, 0b00000, 0b00000, 0b00100, 0b01000], // ','
+    [0b00000, 0b00000, 0b01110, 0b00000, 0b00000], // '-'
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00100], // '.'
+    [0b00001, 0b00010, 0b00100, 0b01000, 0b10000], // '/'
+    [0b01100, 0b10010, 0b10010, 0b10010, 0b01100], // '0'
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b01110], // '1'
+    [0b11100, 0b00010, 0b01100, 0b10000, 0b11110], // '2'
+    [0b11110, 0b00010, 0b00100, 0b10010, 0b01100], // '3'
+    [0b00110, 0b01010, 0b10010, 0b11111, 0b00010], // '4'
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b11110], // '5'
+    [0b00010, 0b00100, 0b01110, 0b10001, 0b01110], // '6'
+    [0b11111, 0b00010, 0b00100, 0b01000, 0b10000], // '7'
+    [0b01110, 0b10001, 0b01110, 0b10001, 0b01110], // '8'
+    [0b01110, 0b10001, 0b01110, 0b00100, 0b01000], // '9'
+    [0b00000, 0b00100, 0b00000, 0b00100, 0b00000], // ':'
+    [0b00000, 0b00100, 0b00000, 0b00100, 0b01000], // ';'
+    [0b00010, 0b00100, 0b01000, 0b00100, 0b00010], // '<'
+    [0b00000, 0b01110, 0b00000, 0b01110, 0b00000], // '='
+    [0b01000, 0b00100, 0b00010, 0b00100, 0b01000], // '>'
+    [0b01110, 0b00001, 0b00110, 0b00000, 0b00100], // '?'
+    [0b01110, 0b10001, 0b10101, 0b10111, 0b01100], // '@'
+    [0b01100, 0b10010, 0b11110, 0b10010, 0b10010], // 'A'
+    [0b11100, 0b10010, 0b11100, 0b10010, 0b11100], // 'B'
+    [0b01110, 0b10000, 0b10000, 0b10000, 0b01110], // 'C'
+    [0b11100, 0b10010, 0b10010, 0b10010, 0b11100], // 'D'
+    [0b11110, 0b10000, 0b11100, 0b10000, 0b11110], // 'E'
+    [0b11110, 0b10000, 0b11100, 0b10000, 0b10000], // 'F'
+    [0b01110, 0b10000, 0b10011, 0b10001, 0b01110], // 'G'
+    [0b10010, 0b10010, 0b11110, 0b10010, 0b10010], // 'H'
+    [0b11100, 0b01000, 0b01000, 0b01000, 0b11100], // 'I'
+    [0b11111, 0b00010, 0b00010, 0b10010, 0b01100], // 'J'
+    [0b10010, 0b10100, 0b11000, 0b10100, 0b10010], // 'K'
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b11110], // 'L'
+    [0b10001, 0b11011, 0b10101, 0b10001, 0b10001], // 'M'
+    [0b10001, 0b11001, 0b10101, 0b10011, 0b10001], // 'N'
+    [0b01100, 0b10010, 0b10010, 0b10010, 0b01100], // 'O'
+    [0b11100, 0b10010, 0b11100, 0b10000, 0b10000], // 'P'
+    [0b01100, 0b10010, 0b10010, 0b01100, 0b00011], // 'Q'
+    [0b11100, 0b10010, 0b11100, 0b10100, 0b10010], // 'R'
+    [0b01110, 0b10000, 0b01100, 0b00010, 0b11100], // 'S'
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100], // 'T'
+    [0b10010, 0b10010, 0b10010, 0b10010, 0b01100], // 'U'
+    [0b10001, 0b10001, 0b10001, 0b01010, 0b00100], // 'V'
+    [0b10001, 0b10001, 0b10101, 0b11011, 0b10001], // 'W'
+    [0b10010, 0b10010, 0b01100, 0b10010, 0b10010], // 'X'
+    [0b10001, 0b01010, 0b00100, 0b00100, 0b00100], // 'Y'
+    [0b11110, 0b00100, 0b01000, 0b10000, 0b11110], // 'Z'
+    [0b01110, 0b01000, 0b01000, 0b01000, 0b01110], // '['
+    [0b10000, 0b01000, 0b00100, 0b00010, 0b00001], // '\\'
+    [0b01110, 0b00010, 0b00010, 0b00010, 0b01110], // ']'
+    [0b00100, 0b01010, 0b00000, 0b00000, 0b00000], // '^'
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b11111], // '_'
+    [0b01000, 0b00100, 0b00000, 0b00000, 0b00000], // '`'
+    [0b00000, 0b01110, 0b10010, 0b10010, 0b01111], // 'a'
+    [0b10000, 0b10000, 0b11100, 0b10010, 0b11100], // 'b'
+    [0b00000, 0b01110, 0b10000, 0b10000, 0b01110], // 'c'
+    [0b00010, 0b00010, 0b01110, 0b10010, 0b01110], // 'd'
+    [0b01100, 0b10010, 0b11100, 0b10000, 0b01110], // 'e'
+    [0b00110, 0b01000, 0b11100, 0b01000, 0b01000], // 'f'
+    [0b01110, 0b10010, 0b01110, 0b00010, 0b01100], // 'g'
+    [0b10000, 0b10000, 0b11100, 0b10010, 0b10010], // 'h'
+    [0b01000, 0b00000, 0b01000, 0b01000, 0b01000], // 'i'
+    [0b00010, 0b00000, 0b00010, 0b10010, 0b01100], // 'j'
+    [0b10000, 0b10100, 0b11000, 0b10100, 0b10010], // 'k'
+    [0b01000, 0b01000, 0b01000, 0b01000, 0b00110], // 'l'
+    [0b00000, 0b11010, 0b10101, 0b10101, 0b10101], // 'm'
+    [0b00000, 0b11100, 0b10010, 0b10010, 0b10010], // 'n'
+    [0b00000, 0b01100, 0b10010, 0b10010, 0b01100], // 'o'
+    [0b00000, 0b11100, 0b10010, 0b11100, 0b10000], // 'p'
+    [0b00000, 0b01110, 0b10010, 0b01110, 0b00010], // 'q'
+    [0b00000, 0b01110, 0b10000, 0b10000, 0b10000], // 'r'
+    [0b00000, 0b00110, 0b01000, 0b00100, 0b11000], // 's'
+    [0b01000, 0b01110, 0b01000, 0b01000, 0b00110], // 't'
+    [0b00000, 0b10010, 0b10010, 0b10010, 0b01110], // 'u'
+    [0b00000, 0b10001, 0b10001, 0b01010, 0b00100], // 'v'
+    [0b00000, 0b10001, 0b10101, 0b10101, 0b01010], // 'w'
+    [0b00000, 0b10010, 0b01100, 0b01100, 0b10010], // 'x'
+    [0b00000, 0b10001, 0b01010, 0b00100, 0b11000], // 'y'
+    [0b00000, 0b11110, 0b00100, 0b01000, 0b11110], // 'z'
+    [0b00110, 0b00100, 0b01100, 0b00100, 0b00110], // '{'
+    [0b00100, 0b00100, 0b00100, 0b00100, 0b00100], // '|'
+    [0b01100, 0b00100, 0b00110, 0b00100, 0b01100], // '}'
+    [0b00000, 0b00000, 0b01101, 0b10010, 0b00000], // '~'
+];
+
+/// Returns the glyph for `c` as 5 rows of bits, where bit 4 is the leftmost column. Characters not
+/// in the font are shown as `?`.
+pub fn glyph(c: char) -> [u8; 5] {
+    let c = if (FIRST as char..=LAST as char).contains(&c) {
+        c as u8
+    } else {
+        b'?'
+    };
+    GLYPHS[(c - FIRST) as usize]
+}
+
+/// Render the glyph for `c` as an image at full brightness.
+pub fn character(c: char) -> Image {
+    let mut image = Image::blank();
+    for (y, row) in glyph(c).iter().enumerate() {
+        for x in 0..5 {
+            if row & (0b10000 >> x) != 0 {
+                image.set_pixel(x, y, MAX_BRIGHTNESS);
+            }
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_character() {
+        let image = character('T');
+        for x in 0..5 {
+            assert_eq!(image.get_pixel(x, 0), MAX_BRIGHTNESS);
+        }
+        for y in 1..5 {
+            assert_eq!(image.get_pixel(2, y), MAX_BRIGHTNESS);
+            assert_eq!(image.get_pixel(1, y), 0);
+        }
+    }
+
+    #[test]
+    fn unknown_character() {
+        assert_eq!(glyph('\u{e9}'), glyph('?'));
+        assert_eq!(glyph('\n'), glyph('?'));
+        assert_eq!(character(' '), Image::blank());
+    }
+}
@@ -0,0 +1,129 @@
+//! Support for drawing on the LED matrix with [embedded-graphics](https://docs.rs/embedded-graphics).
+//!
+//! `BinaryColor::On` is drawn at full brightness, and `Gray4` values are scaled to the brightness
+//! levels of the matrix.
+
+use core::convert::Infallible;
+use core::fmt::Debug;
+
+use embedded_graphics::{
+    drawable::Pixel,
+    geometry::{Point, Size},
+    pixelcolor::{BinaryColor, Gray4, GrayColor},
+    DrawTarget,
+};
+
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::{LedMatrix, MAX_BRIGHTNESS};
+
+impl<P> DrawTarget<BinaryColor> for LedMatrix<P>
+where
+    P: OutputPin,
+    P::Error: Debug,
+{
+    type Error = Infallible;
+
+    fn draw_pixel(&mut self, Pixel(point, color): Pixel<BinaryColor>) -> Result<(), Infallible> {
+        if let Some((x, y)) = to_coordinates(point) {
+            let brightness = if color.is_on() { MAX_BRIGHTNESS } else { 0 };
+            self.set_brightness(x, y, brightness);
+        }
+        Ok(())
+    }
+
+    fn size(&self) -> Size {
+        Size::new(5, 5)
+    }
+}
+
+impl<P> DrawTarget<Gray4> for LedMatrix<P>
+where
+    P: OutputPin,
+    P::Error: Debug,
+{
+    type Error = Infallible;
+
+    fn draw_pixel(&mut self, Pixel(point, color): Pixel<Gray4>) -> Result<(), Infallible> {
+        if let Some((x, y)) = to_coordinates(point) {
+            self.set_brightness(x, y, gray_to_brightness(color));
+        }
+        Ok(())
+    }
+
+    fn size(&self) -> Size {
+        Size::new(5, 5)
+    }
+}
+
+/// Map a point to matrix coordinates, ignoring points outside the display.
+fn to_coordinates(point: Point) -> Option<(usize, usize)> {
+    if (0..5).contains(&point.x) && (0..5).contains(&point.y) {
+        Some((point.x as usize, point.y as usize))
+    } else {
+        None
+    }
+}
+
+/// Scale the 16 levels of `Gray4` to the brightness levels of the matrix, rounding to nearest.
+fn gray_to_brightness(color: Gray4) -> u8 {
+    ((color.luma() as u16 * MAX_BRIGHTNESS as u16 + 7) / 15) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockPin, PinRecorder};
+
+    fn matrix() -> LedMatrix<MockPin> {
+        let (rows, cols) = PinRecorder::new().pins();
+        LedMatrix::from_pins(rows, cols)
+    }
+
+    #[test]
+    fn outside_display_is_ignored() {
+        let mut m = matrix();
+        for &(x, y) in &[
+            (-1, 0),
+            (0, -1),
+            (5, 0),
+            (0, 5),
+            (5, 5),
+            (i32::MAX, i32::MIN),
+        ] {
+            m.draw_pixel(Pixel(Point::new(x, y), BinaryColor::On))
+                .unwrap();
+            m.draw_pixel(Pixel(Point::new(x, y), Gray4::WHITE)).unwrap();
+        }
+        for y in 0..5 {
+            for x in 0..5 {
+                assert!(!m.is_on(x, y));
+            }
+        }
+
+        m.draw_pixel(Pixel(Point::new(4, 4), BinaryColor::On))
+            .unwrap();
+        assert_eq!(m.brightness(4, 4), MAX_BRIGHTNESS);
+    }
+
+    #[test]
+    fn gray_levels() {
+        let mut m = matrix();
+        m.draw_pixel(Pixel(Point::new(0, 0), Gray4::new(0)))
+            .unwrap();
+        m.draw_pixel(Pixel(Point::new(1, 0), Gray4::new(15)))
+            .unwrap();
+        m.draw_pixel(Pixel(Point::new(2, 0), Gray4::new(1)))
+            .unwrap();
+        m.draw_pixel(Pixel(Point::new(3, 0), Gray4::new(14)))
+            .unwrap();
+        assert_eq!(m.brightness(0, 0), 0);
+        assert_eq!(m.brightness(1, 0), MAX_BRIGHTNESS);
+        assert_eq!(m.brightness(2, 0), 1);
+        assert_eq!(m.brightness(3, 0), MAX_BRIGHTNESS - 1);
+
+        m.draw_pixel(Pixel(Point::new(1, 0), BinaryColor::Off))
+            .unwrap();
+        assert_eq!(m.brightness(1, 0), 0);
+    }
+}
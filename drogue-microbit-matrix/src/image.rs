@@ -0,0 +1,625 @@
+use core::str::FromStr;
+
+use crate::MAX_BRIGHTNESS;
+
+/// A 5x5 image, with a brightness between 0 and `MAX_BRIGHTNESS` for each pixel.
+///
+/// Images can be parsed from the same string syntax as MicroPython, where each row is a string of
+/// digits separated by `:`, i.e. `"09090:99999:99999:09990:00900".parse::<Image>()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Image {
+    pixels: [[u8; 5]; 5],
+}
+
+/// Errors returned when parsing an `Image` from a string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// A pixel was not a digit between 0 and 9.
+    InvalidPixel(char),
+    /// A row did not have exactly 5 pixels.
+    InvalidWidth,
+    /// The image did not have exactly 5 rows.
+    InvalidHeight,
+}
+
+impl Image {
+    /// Create an image from rows of pixel brightness values.
+    pub const fn new(pixels: [[u8; 5]; 5]) -> Image {
+        Image { pixels }
+    }
+
+    /// An image with all pixels turned off.
+    pub const fn blank() -> Image {
+        Image::new([[0; 5]; 5])
+    }
+
+    /// Returns the brightness of the pixel at column `x` and row `y`.
+    pub fn get_pixel(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y][x]
+    }
+
+    /// Set the brightness of the pixel at column `x` and row `y`. Values above `MAX_BRIGHTNESS` are
+    /// clamped.
+    pub fn set_pixel(&mut self, x: usize, y: usize, brightness: u8) {
+        self.pixels[y][x] = core::cmp::min(brightness, MAX_BRIGHTNESS);
+    }
+
+    /// The pixel brightness values, indexed by row and then column.
+    pub fn pixels(&self) -> &[[u8; 5]; 5] {
+        &self.pixels
+    }
+}
+
+impl Default for Image {
+    fn default() -> Image {
+        Image::blank()
+    }
+}
+
+impl FromStr for Image {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Image, ParseError> {
+        let mut image = Image::blank();
+        let mut y = 0;
+        for row in s.split(&[':', '\n'][..]) {
+            // MicroPython allows a trailing separator after the last row
+            if row.is_empty() && y == 5 {
+                continue;
+            }
+            if y >= 5 {
+                return Err(ParseError::InvalidHeight);
+            }
+            let mut x = 0;
+            for c in row.chars() {
+                if x >= 5 {
+                    return Err(ParseError::InvalidWidth);
+                }
+                let brightness = c.to_digit(10).ok_or(ParseError::InvalidPixel(c))?;
+                image.pixels[y][x] = brightness as u8;
+                x += 1;
+            }
+            if x != 5 {
+                return Err(ParseError::InvalidWidth);
+            }
+            y += 1;
+        }
+        if y != 5 {
+            return Err(ParseError::InvalidHeight);
+        }
+        Ok(image)
+    }
+}
+
+/// The built-in icons of the micro:bit.
+impl Image {
+    pub const HEART: Image = Image::new([
+        [0, 9, 0, 9, 0],
+        [9, 9, 9, 9, 9],
+        [9, 9, 9, 9, 9],
+        [0, 9, 9, 9, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const HEART_SMALL: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 9, 0, 9, 0],
+        [0, 9, 9, 9, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const HAPPY: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 9, 0, 9, 0],
+        [0, 0, 0, 0, 0],
+        [9, 0, 0, 0, 9],
+        [0, 9, 9, 9, 0],
+    ]);
+    pub const SMILE: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [9, 0, 0, 0, 9],
+        [0, 9, 9, 9, 0],
+    ]);
+    pub const SAD: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 9, 0, 9, 0],
+        [0, 0, 0, 0, 0],
+        [0, 9, 9, 9, 0],
+        [9, 0, 0, 0, 9],
+    ]);
+    pub const CONFUSED: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 9, 0, 9, 0],
+        [0, 0, 0, 0, 0],
+        [0, 9, 0, 9, 0],
+        [9, 0, 9, 0, 9],
+    ]);
+    pub const ANGRY: Image = Image::new([
+        [9, 0, 0, 0, 9],
+        [0, 9, 0, 9, 0],
+        [0, 0, 0, 0, 0],
+        [9, 9, 9, 9, 9],
+        [9, 0, 9, 0, 9],
+    ]);
+    pub const ASLEEP: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [9, 9, 0, 9, 9],
+        [0, 0, 0, 0, 0],
+        [0, 9, 9, 9, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const SURPRISED: Image = Image::new([
+        [0, 9, 0, 9, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 9, 0, 9, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const SILLY: Image = Image::new([
+        [9, 0, 0, 0, 9],
+        [0, 0, 0, 0, 0],
+        [9, 9, 9, 9, 9],
+        [0, 0, 9, 0, 9],
+        [0, 0, 9, 9, 9],
+    ]);
+    pub const FABULOUS: Image = Image::new([
+        [9, 9, 9, 9, 9],
+        [9, 9, 0, 9, 9],
+        [0, 0, 0, 0, 0],
+        [0, 9, 0, 9, 0],
+        [0, 9, 9, 9, 0],
+    ]);
+    pub const MEH: Image = Image::new([
+        [0, 9, 0, 9, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 9, 0],
+        [0, 0, 9, 0, 0],
+        [0, 9, 0, 0, 0],
+    ]);
+    pub const YES: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 9],
+        [0, 0, 0, 9, 0],
+        [9, 0, 9, 0, 0],
+        [0, 9, 0, 0, 0],
+    ]);
+    pub const NO: Image = Image::new([
+        [9, 0, 0, 0, 9],
+        [0, 9, 0, 9, 0],
+        [0, 0, 9, 0, 0],
+        [0, 9, 0, 9, 0],
+        [9, 0, 0, 0, 9],
+    ]);
+    pub const CLOCK12: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const CLOCK1: Image = Image::new([
+        [0, 0, 0, 9, 0],
+        [0, 0, 0, 9, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const CLOCK2: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 9, 9],
+        [0, 0, 9, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const CLOCK3: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 9, 9, 9],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const CLOCK4: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 0, 9, 9],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const CLOCK5: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 0, 9, 0],
+        [0, 0, 0, 9, 0],
+    ]);
+    pub const CLOCK6: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const CLOCK7: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 9, 0, 0, 0],
+        [0, 9, 0, 0, 0],
+    ]);
+    pub const CLOCK8: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+        [9, 9, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const CLOCK9: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [9, 9, 9, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const CLOCK10: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [9, 9, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const CLOCK11: Image = Image::new([
+        [0, 9, 0, 0, 0],
+        [0, 9, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const ARROW_N: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 9, 9, 9, 0],
+        [9, 0, 9, 0, 9],
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const ARROW_NE: Image = Image::new([
+        [0, 0, 9, 9, 9],
+        [0, 0, 0, 9, 9],
+        [0, 0, 9, 0, 9],
+        [0, 9, 0, 0, 0],
+        [9, 0, 0, 0, 0],
+    ]);
+    pub const ARROW_E: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 0, 0, 9, 0],
+        [9, 9, 9, 9, 9],
+        [0, 0, 0, 9, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const ARROW_SE: Image = Image::new([
+        [9, 0, 0, 0, 0],
+        [0, 9, 0, 0, 0],
+        [0, 0, 9, 0, 9],
+        [0, 0, 0, 9, 9],
+        [0, 0, 9, 9, 9],
+    ]);
+    pub const ARROW_S: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+        [9, 0, 9, 0, 9],
+        [0, 9, 9, 9, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const ARROW_SW: Image = Image::new([
+        [0, 0, 0, 0, 9],
+        [0, 0, 0, 9, 0],
+        [9, 0, 9, 0, 0],
+        [9, 9, 0, 0, 0],
+        [9, 9, 9, 0, 0],
+    ]);
+    pub const ARROW_W: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 9, 0, 0, 0],
+        [9, 9, 9, 9, 9],
+        [0, 9, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const ARROW_NW: Image = Image::new([
+        [9, 9, 9, 0, 0],
+        [9, 9, 0, 0, 0],
+        [9, 0, 9, 0, 0],
+        [0, 0, 0, 9, 0],
+        [0, 0, 0, 0, 9],
+    ]);
+    pub const TRIANGLE: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 9, 0, 9, 0],
+        [9, 9, 9, 9, 9],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const TRIANGLE_LEFT: Image = Image::new([
+        [9, 0, 0, 0, 0],
+        [9, 9, 0, 0, 0],
+        [9, 0, 9, 0, 0],
+        [9, 0, 0, 9, 0],
+        [9, 9, 9, 9, 9],
+    ]);
+    pub const CHESSBOARD: Image = Image::new([
+        [0, 9, 0, 9, 0],
+        [9, 0, 9, 0, 9],
+        [0, 9, 0, 9, 0],
+        [9, 0, 9, 0, 9],
+        [0, 9, 0, 9, 0],
+    ]);
+    pub const DIAMOND: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 9, 0, 9, 0],
+        [9, 0, 0, 0, 9],
+        [0, 9, 0, 9, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const DIAMOND_SMALL: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 9, 0, 9, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const SQUARE: Image = Image::new([
+        [9, 9, 9, 9, 9],
+        [9, 0, 0, 0, 9],
+        [9, 0, 0, 0, 9],
+        [9, 0, 0, 0, 9],
+        [9, 9, 9, 9, 9],
+    ]);
+    pub const SQUARE_SMALL: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 9, 9, 9, 0],
+        [0, 9, 0, 9, 0],
+        [0, 9, 9, 9, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const RABBIT: Image = Image::new([
+        [9, 0, 9, 0, 0],
+        [9, 0, 9, 0, 0],
+        [9, 9, 9, 9, 0],
+        [9, 9, 0, 9, 0],
+        [9, 9, 9, 9, 0],
+    ]);
+    pub const COW: Image = Image::new([
+        [9, 0, 0, 0, 9],
+        [9, 0, 0, 0, 9],
+        [9, 9, 9, 9, 9],
+        [0, 9, 9, 9, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const MUSIC_CROTCHET: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+        [9, 9, 9, 0, 0],
+        [9, 9, 9, 0, 0],
+    ]);
+    pub const MUSIC_QUAVER: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 9, 0],
+        [0, 0, 9, 0, 9],
+        [9, 9, 9, 0, 0],
+        [9, 9, 9, 0, 0],
+    ]);
+    pub const MUSIC_QUAVERS: Image = Image::new([
+        [0, 9, 9, 9, 9],
+        [0, 9, 0, 0, 9],
+        [0, 9, 0, 0, 9],
+        [9, 9, 0, 9, 9],
+        [9, 9, 0, 9, 9],
+    ]);
+    pub const PITCHFORK: Image = Image::new([
+        [9, 0, 9, 0, 9],
+        [9, 0, 9, 0, 9],
+        [9, 9, 9, 9, 9],
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const XMAS: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 9, 9, 9, 0],
+        [0, 0, 9, 0, 0],
+        [0, 9, 9, 9, 0],
+        [9, 9, 9, 9, 9],
+    ]);
+    pub const PACMAN: Image = Image::new([
+        [0, 9, 9, 9, 9],
+        [9, 9, 0, 9, 0],
+        [9, 9, 9, 0, 0],
+        [9, 9, 9, 9, 0],
+        [0, 9, 9, 9, 9],
+    ]);
+    pub const TARGET: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 9, 9, 9, 0],
+        [9, 9, 0, 9, 9],
+        [0, 9, 9, 9, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const TSHIRT: Image = Image::new([
+        [9, 9, 0, 9, 9],
+        [9, 9, 9, 9, 9],
+        [0, 9, 9, 9, 0],
+        [0, 9, 9, 9, 0],
+        [0, 9, 9, 9, 0],
+    ]);
+    pub const ROLLERSKATE: Image = Image::new([
+        [0, 0, 0, 9, 9],
+        [0, 0, 0, 9, 9],
+        [9, 9, 9, 9, 9],
+        [9, 9, 9, 9, 9],
+        [0, 9, 0, 9, 0],
+    ]);
+    pub const DUCK: Image = Image::new([
+        [0, 9, 9, 0, 0],
+        [9, 9, 9, 0, 0],
+        [0, 9, 9, 9, 9],
+        [0, 9, 9, 9, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const HOUSE: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 9, 9, 9, 0],
+        [9, 9, 9, 9, 9],
+        [0, 9, 9, 9, 0],
+        [0, 9, 0, 9, 0],
+    ]);
+    pub const TORTOISE: Image = Image::new([
+        [0, 0, 0, 0, 0],
+        [0, 9, 9, 9, 0],
+        [9, 9, 9, 9, 9],
+        [0, 9, 0, 9, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+    pub const BUTTERFLY: Image = Image::new([
+        [9, 9, 0, 9, 9],
+        [9, 9, 9, 9, 9],
+        [0, 0, 9, 0, 0],
+        [9, 9, 9, 9, 9],
+        [9, 9, 0, 9, 9],
+    ]);
+    pub const STICKFIGURE: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [9, 9, 9, 9, 9],
+        [0, 0, 9, 0, 0],
+        [0, 9, 0, 9, 0],
+        [9, 0, 0, 0, 9],
+    ]);
+    pub const GHOST: Image = Image::new([
+        [9, 9, 9, 9, 9],
+        [9, 0, 9, 0, 9],
+        [9, 9, 9, 9, 9],
+        [9, 9, 9, 9, 9],
+        [9, 0, 9, 0, 9],
+    ]);
+    pub const SWORD: Image = Image::new([
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 0, 9, 0, 0],
+        [0, 9, 9, 9, 0],
+        [0, 0, 9, 0, 0],
+    ]);
+    pub const GIRAFFE: Image = Image::new([
+        [9, 9, 0, 0, 0],
+        [0, 9, 0, 0, 0],
+        [0, 9, 0, 0, 0],
+        [0, 9, 9, 9, 0],
+        [0, 9, 0, 9, 0],
+    ]);
+    pub const SKULL: Image = Image::new([
+        [0, 9, 9, 9, 0],
+        [9, 0, 9, 0, 9],
+        [9, 9, 9, 9, 9],
+        [0, 9, 9, 9, 0],
+        [0, 9, 9, 9, 0],
+    ]);
+    pub const UMBRELLA: Image = Image::new([
+        [0, 9, 9, 9, 0],
+        [9, 9, 9, 9, 9],
+        [0, 0, 9, 0, 0],
+        [9, 0, 9, 0, 0],
+        [0, 9, 9, 0, 0],
+    ]);
+    pub const SNAKE: Image = Image::new([
+        [9, 9, 0, 0, 0],
+        [9, 9, 0, 9, 9],
+        [0, 9, 0, 9, 0],
+        [0, 9, 9, 9, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+
+    /// The clock faces, starting at 12 o'clock.
+    pub const ALL_CLOCKS: [Image; 12] = [
+        Image::CLOCK12,
+        Image::CLOCK1,
+        Image::CLOCK2,
+        Image::CLOCK3,
+        Image::CLOCK4,
+        Image::CLOCK5,
+        Image::CLOCK6,
+        Image::CLOCK7,
+        Image::CLOCK8,
+        Image::CLOCK9,
+        Image::CLOCK10,
+        Image::CLOCK11,
+    ];
+
+    /// The arrows, starting at north and going clockwise.
+    pub const ALL_ARROWS: [Image; 8] = [
+        Image::ARROW_N,
+        Image::ARROW_NE,
+        Image::ARROW_E,
+        Image::ARROW_SE,
+        Image::ARROW_S,
+        Image::ARROW_SW,
+        Image::ARROW_W,
+        Image::ARROW_NW,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_image() {
+        let image: Image = "09090:99999:99999:09990:00900".parse().unwrap();
+        assert_eq!(image, Image::HEART);
+        assert_eq!(image.get_pixel(1, 0), 9);
+        assert_eq!(image.get_pixel(0, 0), 0);
+        assert_eq!(image.get_pixel(2, 4), 9);
+    }
+
+    #[test]
+    fn parse_brightness_levels() {
+        let image: Image = "01234:56789:00000:00000:00000:".parse().unwrap();
+        for x in 0..5 {
+            assert_eq!(image.get_pixel(x, 0), x as u8);
+            assert_eq!(image.get_pixel(x, 1), x as u8 + 5);
+        }
+    }
+
+    #[test]
+    fn parse_newline_separated() {
+        let image: Image = "00000\n09090\n00000\n90009\n09990".parse().unwrap();
+        assert_eq!(image, Image::HAPPY);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "0909:99999:99999:09990:00900".parse::<Image>(),
+            Err(ParseError::InvalidWidth)
+        );
+        assert_eq!(
+            "090900:99999:99999:09990:00900".parse::<Image>(),
+            Err(ParseError::InvalidWidth)
+        );
+        assert_eq!(
+            "09090:99999:99999:09990".parse::<Image>(),
+            Err(ParseError::InvalidHeight)
+        );
+        assert_eq!(
+            "09090:99999:99999:09990:00900:00000".parse::<Image>(),
+            Err(ParseError::InvalidHeight)
+        );
+        assert_eq!(
+            "0909a:99999:99999:09990:00900".parse::<Image>(),
+            Err(ParseError::InvalidPixel('a'))
+        );
+    }
+
+    #[test]
+    fn set_pixel_clamps_brightness() {
+        let mut image = Image::blank();
+        image.set_pixel(4, 3, 200);
+        assert_eq!(image.get_pixel(4, 3), MAX_BRIGHTNESS);
+    }
+}
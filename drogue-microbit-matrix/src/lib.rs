@@ -1,48 +1,210 @@
 #![no_std]
 
+use core::fmt::Debug;
 use embedded_hal::digital::v2::OutputPin;
-use hal::gpio::{Level, Output, Pin, PushPull};
 
-use nrf51_hal as hal;
+#[cfg(all(feature = "v1", feature = "layout-v2"))]
+compile_error!("Only one of the `v1` and `v2` features can be enabled");
 
-pub struct LedMatrix {
-    rows: [Pin<Output<PushPull>>; 3],
-    cols: [Pin<Output<PushPull>>; 9],
+mod animation;
+pub mod font;
+#[cfg(feature = "embedded-graphics")]
+mod graphics;
+mod image;
+#[cfg(test)]
+mod mock;
+mod orientation;
+mod scroll;
+#[cfg(not(feature = "layout-v2"))]
+mod v1;
+#[cfg(feature = "layout-v2")]
+mod v2;
+
+#[cfg(not(feature = "layout-v2"))]
+use v1 as board;
+#[cfg(feature = "layout-v2")]
+use v2 as board;
+
+pub use animation::{Animation, AnimationMode, AnimationPlayer, Frame};
+pub use board::{COLS, ROWS};
+pub use image::{Image, ParseError};
+pub use orientation::{Flip, Rotation};
+pub use scroll::{ScrollingText, MAX_SCROLL_LEN};
+
+/// The brightness of a fully lit pixel.
+pub const MAX_BRIGHTNESS: u8 = 9;
+
+/// Driver for the 5x5 LED matrix on the micro:bit.
+///
+/// The 25 LEDs are electrically wired as a matrix of `ROWS` x `COLS`, which is 3x9 on the micro:bit
+/// v1 and 5x5 on the v2. Only one hardware row can be lit at a time, so pixels are written to a
+/// frame buffer, and `refresh` must be called periodically (i.e. from a timer interrupt) to scan the
+/// rows in turn.
+///
+/// Each pixel has a brightness between 0 (off) and `MAX_BRIGHTNESS`. Brightness is implemented by
+/// keeping each row lit for `MAX_BRIGHTNESS` refresh periods, and only driving a pixel for as many
+/// of those periods as its brightness.
+///
+/// The image can be rotated, mirrored and inverted with `set_orientation` and `set_inverted`, for
+/// when the micro:bit is mounted in another orientation. This only changes how the frame buffer is
+/// mapped to the display, so pixels are still addressed in the application's own frame.
+///
+/// The driver is generic over the pin type, so that it can be tested without hardware. Use
+/// `LedMatrix::new` to create it from the GPIO ports of the board selected by the `v1` or `v2`
+/// feature.
+pub struct LedMatrix<P> {
+    rows: [P; ROWS],
+    cols: [P; COLS],
     coordinates: [[(usize, usize); 5]; 5],
+    frame: [[u8; 5]; 5],
+    row: usize,
+    phase: u8,
+    inverted: bool,
+    scroller: Option<ScrollingText>,
 }
 
-impl LedMatrix {
-    pub fn new(ports: hal::gpio::p0::Parts) -> LedMatrix {
+impl<P> LedMatrix<P>
+where
+    P: OutputPin,
+    P::Error: Debug,
+{
+    /// Create a matrix driving the given hardware rows (active high) and columns (active low).
+    pub fn from_pins(rows: [P; ROWS], cols: [P; COLS]) -> Self {
         let mut m = LedMatrix {
-            rows: [
-                ports.p0_13.into_push_pull_output(Level::Low).degrade(),
-                ports.p0_14.into_push_pull_output(Level::Low).degrade(),
-                ports.p0_15.into_push_pull_output(Level::Low).degrade(),
-            ],
-            cols: [
-                ports.p0_04.into_push_pull_output(Level::Low).degrade(),
-                ports.p0_05.into_push_pull_output(Level::Low).degrade(),
-                ports.p0_06.into_push_pull_output(Level::Low).degrade(),
-                ports.p0_07.into_push_pull_output(Level::Low).degrade(),
-                ports.p0_08.into_push_pull_output(Level::Low).degrade(),
-                ports.p0_09.into_push_pull_output(Level::Low).degrade(),
-                ports.p0_10.into_push_pull_output(Level::Low).degrade(),
-                ports.p0_11.into_push_pull_output(Level::Low).degrade(),
-                ports.p0_12.into_push_pull_output(Level::Low).degrade(),
-            ],
-            coordinates: [
-                [(0, 0), (1, 3), (0, 1), (1, 4), (0, 2)],
-                [(2, 3), (2, 4), (2, 5), (2, 6), (2, 7)],
-                [(1, 1), (0, 8), (1, 2), (2, 8), (1, 0)],
-                [(0, 7), (0, 6), (0, 5), (0, 4), (0, 3)],
-                [(2, 2), (1, 6), (2, 0), (1, 5), (2, 1)],
-            ],
+            rows,
+            cols,
+            coordinates: board::COORDINATES,
+            frame: [[0; 5]; 5],
+            row: 0,
+            phase: 0,
+            inverted: false,
+            scroller: None,
         };
-        m.clear();
+        m.blank();
         m
     }
 
+    /// Turn off all pixels in the frame buffer. This also stops any scrolling text.
     pub fn clear(&mut self) {
+        self.scroller = None;
+        self.frame = [[0; 5]; 5];
+    }
+
+    /// Turn on the pixel at `row` and `col` at full brightness.
+    #[deprecated(note = "use `set_brightness(x, y, MAX_BRIGHTNESS)`, which takes the column first")]
+    pub fn on(&mut self, row: usize, col: usize) {
+        self.set_brightness(col, row, MAX_BRIGHTNESS);
+    }
+
+    /// Turn off the pixel at `row` and `col`.
+    #[deprecated(note = "use `set_brightness(x, y, 0)`, which takes the column first")]
+    pub fn off(&mut self, row: usize, col: usize) {
+        self.set_brightness(col, row, 0);
+    }
+
+    /// Returns true if the pixel at column `x` and row `y` is lit at all.
+    pub fn is_on(&self, x: usize, y: usize) -> bool {
+        self.frame[y][x] > 0
+    }
+
+    /// Set the brightness of the pixel at column `x` and row `y`. Values above `MAX_BRIGHTNESS` are
+    /// clamped.
+    pub fn set_brightness(&mut self, x: usize, y: usize, brightness: u8) {
+        self.frame[y][x] = core::cmp::min(brightness, MAX_BRIGHTNESS);
+    }
+
+    /// Returns the brightness of the pixel at column `x` and row `y`.
+    pub fn brightness(&self, x: usize, y: usize) -> u8 {
+        self.frame[y][x]
+    }
+
+    /// Replace the frame buffer with the contents of `image`. This also stops any scrolling text.
+    pub fn show(&mut self, image: &Image) {
+        self.scroller = None;
+        self.frame = *image.pixels();
+    }
+
+    /// Set how the frame buffer is rotated and mirrored on the display.
+    pub fn set_orientation(&mut self, rotation: Rotation, flip: Flip) {
+        for y in 0..5 {
+            for x in 0..5 {
+                let (dx, dy) = orientation::transform(x, y, rotation, flip);
+                self.coordinates[y][x] = board::COORDINATES[dy][dx];
+            }
+        }
+    }
+
+    /// Invert the brightness of all pixels on the display, without changing the frame buffer.
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
+    }
+
+    /// Show a single character using the built-in font.
+    pub fn show_char(&mut self, c: char) {
+        self.show(&font::character(c));
+    }
+
+    /// Start scrolling `text` across the display. The text moves one column for every `speed` calls
+    /// to `scroll`, so this does not block and `scroll` can be called from a timer interrupt.
+    pub fn scroll_text(&mut self, text: &str, speed: u32) {
+        let scroller = ScrollingText::new(text, speed);
+        self.frame = *scroller.render().pixels();
+        self.scroller = Some(scroller);
+    }
+
+    /// Advance the scrolling text started by `scroll_text`. Returns true while the text is still
+    /// scrolling.
+    pub fn scroll(&mut self) -> bool {
+        if let Some(scroller) = &mut self.scroller {
+            if scroller.step() {
+                self.frame = *scroller.render().pixels();
+            }
+            if scroller.is_finished() {
+                self.scroller = None;
+            }
+        }
+        self.scroller.is_some()
+    }
+
+    /// Drive the hardware row for the next refresh period according to the frame buffer.
+    ///
+    /// A row stays lit for `MAX_BRIGHTNESS` calls before moving on to the next row, so a full frame
+    /// takes `ROWS * MAX_BRIGHTNESS` calls. To get a steady image, call this at least 4000 times
+    /// per second on the v1 and 7000 times per second on the v2.
+    pub fn refresh(&mut self) {
+        if self.phase == 0 {
+            self.rows[self.row].set_low().unwrap();
+            self.row = (self.row + 1) % self.rows.len();
+
+            for col in self.cols.iter_mut() {
+                col.set_high().unwrap();
+            }
+        }
+
+        for (y, line) in self.coordinates.iter().enumerate() {
+            for (x, &(r, c)) in line.iter().enumerate() {
+                if r == self.row {
+                    let brightness = if self.inverted {
+                        MAX_BRIGHTNESS - self.frame[y][x]
+                    } else {
+                        self.frame[y][x]
+                    };
+                    if brightness > self.phase {
+                        self.cols[c].set_low().unwrap();
+                    } else {
+                        self.cols[c].set_high().unwrap();
+                    }
+                }
+            }
+        }
+
+        if self.phase == 0 {
+            self.rows[self.row].set_high().unwrap();
+        }
+        self.phase = (self.phase + 1) % MAX_BRIGHTNESS;
+    }
+
+    fn blank(&mut self) {
         for row in self.rows.iter_mut() {
             row.set_low().unwrap();
         }
@@ -50,23 +212,177 @@ impl LedMatrix {
             col.set_high().unwrap();
         }
     }
+}
 
-    pub fn on(&mut self, x: usize, y: usize) {
-        let (r, c) = self.coordinates[x][y];
-        self.rows[r].set_high().unwrap();
-        self.cols[c].set_low().unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockPin, PinId, PinRecorder};
+
+    fn matrix() -> (LedMatrix<MockPin>, PinRecorder) {
+        let recorder = PinRecorder::new();
+        let (rows, cols) = recorder.pins();
+        (LedMatrix::from_pins(rows, cols), recorder)
     }
 
-    pub fn off(&mut self, x: usize, y: usize) {
-        let (r, c) = self.coordinates[x][y];
-        self.rows[r].set_low().unwrap();
-        self.cols[c].set_high().unwrap();
+    /// Refresh until the given hardware row is lit, at the start of its refresh periods.
+    fn refresh_until_row(m: &mut LedMatrix<MockPin>, recorder: &PinRecorder, row: usize) {
+        for _ in 0..ROWS {
+            m.refresh();
+            if recorder.lit_rows() == [row] {
+                return;
+            }
+            for _ in 1..MAX_BRIGHTNESS {
+                m.refresh();
+            }
+        }
+        panic!("row {} never lit", row);
+    }
+
+    #[test]
+    fn new_matrix_is_blank() {
+        let (_, recorder) = matrix();
+        assert!(recorder.lit_rows().is_empty());
+        assert!(recorder.lit_cols().is_empty());
+    }
+
+    #[test]
+    fn rows_are_scanned_in_turn() {
+        let (mut m, recorder) = matrix();
+        for expected in (1..=ROWS).map(|r| r % ROWS) {
+            for _ in 0..MAX_BRIGHTNESS {
+                m.refresh();
+                assert_eq!(recorder.lit_rows(), [expected]);
+            }
+        }
+    }
+
+    #[test]
+    fn pixels_map_to_hardware_coordinates() {
+        let (mut m, recorder) = matrix();
+        for (y, line) in board::COORDINATES.iter().enumerate() {
+            for (x, &(row, col)) in line.iter().enumerate() {
+                m.clear();
+                m.set_brightness(x, y, MAX_BRIGHTNESS);
+                refresh_until_row(&mut m, &recorder, row);
+                assert_eq!(recorder.lit_cols(), [col], "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn on_takes_row_first() {
+        let (mut m, _) = matrix();
+        m.on(1, 3);
+        assert!(m.is_on(3, 1));
+        m.off(1, 3);
+        assert!(!m.is_on(3, 1));
+    }
+
+    #[test]
+    fn orientation_changes_mapping() {
+        let (mut m, recorder) = matrix();
+        m.set_orientation(Rotation::Rotate90, Flip::None);
+        m.set_brightness(0, 0, MAX_BRIGHTNESS);
+
+        // The top left pixel is shown in the top right corner
+        let (row, col) = board::COORDINATES[0][4];
+        refresh_until_row(&mut m, &recorder, row);
+        assert_eq!(recorder.lit_cols(), [col]);
+
+        m.set_orientation(Rotation::Rotate0, Flip::Vertical);
+        let (row, col) = board::COORDINATES[4][0];
+        refresh_until_row(&mut m, &recorder, row);
+        assert_eq!(recorder.lit_cols(), [col]);
     }
-}
 
-mod tests {
     #[test]
-    fn it_works() {
-        assert_eq!(2 + 2, 4);
+    fn inverted_display() {
+        let (mut m, recorder) = matrix();
+        m.set_inverted(true);
+        m.set_brightness(0, 0, MAX_BRIGHTNESS);
+
+        let (row, col) = board::COORDINATES[0][0];
+        refresh_until_row(&mut m, &recorder, row);
+        let lit = recorder.lit_cols();
+        assert!(!lit.contains(&col));
+        assert!(!lit.is_empty());
+        assert!(m.is_on(0, 0));
+    }
+
+    #[test]
+    fn brightness_sets_duty_cycle() {
+        let (mut m, recorder) = matrix();
+        let (row, col) = board::COORDINATES[1][3];
+        m.set_brightness(3, 1, 4);
+        refresh_until_row(&mut m, &recorder, row);
+
+        let mut lit = if recorder.lit_cols() == [col] { 1 } else { 0 };
+        for _ in 1..MAX_BRIGHTNESS {
+            m.refresh();
+            if recorder.lit_cols() == [col] {
+                lit += 1;
+            }
+        }
+        assert_eq!(lit, 4);
+    }
+
+    #[test]
+    fn columns_are_set_while_rows_are_off() {
+        let (mut m, recorder) = matrix();
+        m.show(&Image::HEART);
+        recorder.take_writes();
+
+        // Switching rows must turn off the old row before touching the columns, and only turn on
+        // the new row once the columns are set, or the previous row's pixels ghost.
+        m.refresh();
+        let writes = recorder.take_writes();
+        assert_eq!(writes.first(), Some(&(PinId::Row(0), false)));
+        assert_eq!(writes.last(), Some(&(PinId::Row(1), true)));
+        assert!(writes[1..writes.len() - 1]
+            .iter()
+            .all(|(pin, _)| matches!(pin, PinId::Col(_))));
+    }
+
+    #[test]
+    fn show_image() {
+        let (mut m, recorder) = matrix();
+        m.show(&Image::HAPPY);
+
+        let mut lit = 0;
+        for _ in 0..ROWS {
+            m.refresh();
+            lit += recorder.lit_cols().len();
+            for _ in 1..MAX_BRIGHTNESS {
+                m.refresh();
+            }
+        }
+        let expected = Image::HAPPY
+            .pixels()
+            .iter()
+            .flatten()
+            .filter(|&&p| p > 0)
+            .count();
+        assert_eq!(lit, expected);
+    }
+
+    #[test]
+    fn scroll_text() {
+        let (mut m, _) = matrix();
+        m.scroll_text("I", 2);
+        assert_eq!(m.brightness(4, 0), 0);
+
+        let mut steps = 0;
+        while m.scroll() {
+            steps += 1;
+            if steps == 2 {
+                // The first column of the I has entered on the right
+                assert_eq!(m.brightness(4, 0), MAX_BRIGHTNESS);
+                assert_eq!(m.brightness(4, 1), 0);
+            }
+        }
+        assert_eq!(steps, 2 * 10 - 1);
+        assert_eq!(m.brightness(4, 0), 0);
     }
 }
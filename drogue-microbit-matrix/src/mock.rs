@@ -0,0 +1,131 @@
+//! Mock output pins for testing the matrix on the host.
+
+extern crate std;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::vec::Vec;
+
+use core::convert::Infallible;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::{COLS, ROWS};
+
+/// Identifies a mock pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PinId {
+    Row(usize),
+    Col(usize),
+}
+
+#[derive(Default)]
+struct State {
+    levels: Vec<(PinId, bool)>,
+    writes: Vec<(PinId, bool)>,
+}
+
+/// Records the level of, and every write to, the pins it has created.
+#[derive(Clone, Default)]
+pub struct PinRecorder {
+    state: Rc<RefCell<State>>,
+}
+
+/// An output pin that reports to a `PinRecorder`.
+pub struct MockPin {
+    id: PinId,
+    state: Rc<RefCell<State>>,
+}
+
+impl PinRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the row and column pins for the selected board, which start out high.
+    pub fn pins(&self) -> ([MockPin; ROWS], [MockPin; COLS]) {
+        let row = |i| self.pin(PinId::Row(i));
+        let col = |i| self.pin(PinId::Col(i));
+        #[cfg(not(feature = "layout-v2"))]
+        return (
+            [row(0), row(1), row(2)],
+            [
+                col(0),
+                col(1),
+                col(2),
+                col(3),
+                col(4),
+                col(5),
+                col(6),
+                col(7),
+                col(8),
+            ],
+        );
+        #[cfg(feature = "layout-v2")]
+        return (
+            [row(0), row(1), row(2), row(3), row(4)],
+            [col(0), col(1), col(2), col(3), col(4)],
+        );
+    }
+
+    fn pin(&self, id: PinId) -> MockPin {
+        self.state.borrow_mut().levels.push((id, true));
+        MockPin {
+            id,
+            state: self.state.clone(),
+        }
+    }
+
+    /// Returns true if the pin is currently high.
+    pub fn is_high(&self, id: PinId) -> bool {
+        self.state
+            .borrow()
+            .levels
+            .iter()
+            .find(|(pin, _)| *pin == id)
+            .map(|(_, level)| *level)
+            .unwrap()
+    }
+
+    /// Returns the hardware rows that are currently driven high.
+    pub fn lit_rows(&self) -> Vec<usize> {
+        (0..ROWS).filter(|&r| self.is_high(PinId::Row(r))).collect()
+    }
+
+    /// Returns the hardware columns that are currently driven low.
+    pub fn lit_cols(&self) -> Vec<usize> {
+        (0..COLS)
+            .filter(|&c| !self.is_high(PinId::Col(c)))
+            .collect()
+    }
+
+    /// Returns and forgets all writes made since the last call.
+    pub fn take_writes(&self) -> Vec<(PinId, bool)> {
+        core::mem::take(&mut self.state.borrow_mut().writes)
+    }
+}
+
+impl MockPin {
+    fn set(&mut self, level: bool) {
+        let mut state = self.state.borrow_mut();
+        for (pin, current) in state.levels.iter_mut() {
+            if *pin == self.id {
+                *current = level;
+            }
+        }
+        state.writes.push((self.id, level));
+    }
+}
+
+impl OutputPin for MockPin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.set(true);
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+/// Rotation of the image on the display, clockwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    Rotate0,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+/// Mirroring of the image on the display. Mirroring is applied before rotation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flip {
+    None,
+    /// Mirror left to right.
+    Horizontal,
+    /// Mirror top to bottom.
+    Vertical,
+}
+
+/// Returns where the pixel at column `x` and row `y` of the image ends up on the display.
+pub(crate) fn transform(x: usize, y: usize, rotation: Rotation, flip: Flip) -> (usize, usize) {
+    let (x, y) = match flip {
+        Flip::None => (x, y),
+        Flip::Horizontal => (4 - x, y),
+        Flip::Vertical => (x, 4 - y),
+    };
+    match rotation {
+        Rotation::Rotate0 => (x, y),
+        Rotation::Rotate90 => (4 - y, x),
+        Rotation::Rotate180 => (4 - x, 4 - y),
+        Rotation::Rotate270 => (y, 4 - x),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate() {
+        // The top left corner moves clockwise around the display
+        assert_eq!(transform(0, 0, Rotation::Rotate0, Flip::None), (0, 0));
+        assert_eq!(transform(0, 0, Rotation::Rotate90, Flip::None), (4, 0));
+        assert_eq!(transform(0, 0, Rotation::Rotate180, Flip::None), (4, 4));
+        assert_eq!(transform(0, 0, Rotation::Rotate270, Flip::None), (0, 4));
+
+        assert_eq!(transform(1, 0, Rotation::Rotate90, Flip::None), (4, 1));
+        assert_eq!(transform(2, 2, Rotation::Rotate270, Flip::None), (2, 2));
+    }
+
+    #[test]
+    fn flip() {
+        assert_eq!(transform(1, 0, Rotation::Rotate0, Flip::Horizontal), (3, 0));
+        assert_eq!(transform(1, 0, Rotation::Rotate0, Flip::Vertical), (1, 4));
+        // Flip is applied before rotation
+        assert_eq!(
+            transform(1, 0, Rotation::Rotate90, Flip::Horizontal),
+            (4, 3)
+        );
+    }
+}
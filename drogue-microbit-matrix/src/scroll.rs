@@ -0,0 +1,154 @@
+use crate::{font, Image, MAX_BRIGHTNESS};
+
+/// The maximum number of characters that can be scrolled. Longer text is truncated.
+pub const MAX_SCROLL_LEN: usize = 64;
+
+/// Width of a character including the blank column that separates it from the next one.
+const CHAR_WIDTH: usize = 6;
+
+/// Text scrolling from right to left across the display, one column at a time.
+///
+/// The scroller does not block: each call to `step` moves the text along, which makes it suitable
+/// for driving from a timer interrupt.
+pub struct ScrollingText {
+    text: [u8; MAX_SCROLL_LEN],
+    len: usize,
+    position: usize,
+    speed: u32,
+    ticks: u32,
+}
+
+impl ScrollingText {
+    /// Create a scroller for `text`. The text moves one column for every `speed` calls to `step`.
+    /// Characters that are not printable ASCII are shown as `?`.
+    pub fn new(text: &str, speed: u32) -> ScrollingText {
+        let mut s = ScrollingText {
+            text: [0; MAX_SCROLL_LEN],
+            len: 0,
+            position: 0,
+            speed: core::cmp::max(speed, 1),
+            ticks: 0,
+        };
+        for c in text.chars().take(MAX_SCROLL_LEN) {
+            s.text[s.len] = if c.is_ascii_graphic() || c == ' ' {
+                c as u8
+            } else {
+                b'?'
+            };
+            s.len += 1;
+        }
+        s
+    }
+
+    /// Returns true once the text has scrolled off the display.
+    pub fn is_finished(&self) -> bool {
+        // The text starts off-screen to the right and the last separator column never needs to be
+        // shown, so it takes 5 + len * CHAR_WIDTH - 1 steps for the text to have passed.
+        self.len == 0 || self.position + 1 >= 5 + self.len * CHAR_WIDTH
+    }
+
+    /// Count one call and move the text by one column if it is time to do so. Returns true if the
+    /// display needs to be redrawn.
+    pub fn step(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        self.ticks += 1;
+        if self.ticks < self.speed {
+            return false;
+        }
+        self.ticks = 0;
+        self.position += 1;
+        true
+    }
+
+    /// Render the currently visible part of the text.
+    pub fn render(&self) -> Image {
+        let mut image = Image::blank();
+        for x in 0..5 {
+            let column = self.position + x;
+            // The first five columns are blank, so that the text enters from the right
+            if column < 5 {
+                continue;
+            }
+            let index = (column - 5) / CHAR_WIDTH;
+            let offset = (column - 5) % CHAR_WIDTH;
+            if index >= self.len || offset >= 5 {
+                continue;
+            }
+            let glyph = font::glyph(self.text[index] as char);
+            for (y, row) in glyph.iter().enumerate() {
+                if row & (0b10000 >> offset) != 0 {
+                    image.set_pixel(x, y, MAX_BRIGHTNESS);
+                }
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(image: &Image, x: usize) -> [u8; 5] {
+        let mut column = [0; 5];
+        for (y, pixel) in column.iter_mut().enumerate() {
+            *pixel = image.get_pixel(x, y);
+        }
+        column
+    }
+
+    #[test]
+    fn text_enters_from_the_right() {
+        let mut text = ScrollingText::new("T", 1);
+        assert_eq!(text.render(), Image::blank());
+
+        // After one step the first column of the T is in the rightmost column
+        assert!(text.step());
+        let image = text.render();
+        assert_eq!(column(&image, 4), [9, 0, 0, 0, 0]);
+        assert_eq!(column(&image, 3), [0; 5]);
+
+        // After 4 more steps the T fills the display
+        for _ in 0..4 {
+            assert!(text.step());
+        }
+        assert_eq!(text.render(), font::character('T'));
+    }
+
+    #[test]
+    fn text_scrolls_off() {
+        let mut text = ScrollingText::new("AB", 1);
+        let mut steps = 0;
+        while text.step() {
+            steps += 1;
+        }
+        assert!(text.is_finished());
+        assert_eq!(steps, 5 + 2 * CHAR_WIDTH - 1);
+        assert_eq!(text.render(), Image::blank());
+    }
+
+    #[test]
+    fn speed_divides_steps() {
+        let mut text = ScrollingText::new("A", 3);
+        assert!(!text.step());
+        assert!(!text.step());
+        assert!(text.step());
+        assert!(!text.step());
+    }
+
+    #[test]
+    fn empty_text_is_finished() {
+        let mut text = ScrollingText::new("", 1);
+        assert!(text.is_finished());
+        assert!(!text.step());
+    }
+
+    #[test]
+    fn long_text_is_truncated() {
+        let long = "0123456789012345678901234567890123456789012345678901234567890123456789";
+        let text = ScrollingText::new(long, 1);
+        assert_eq!(text.len, MAX_SCROLL_LEN);
+    }
+}
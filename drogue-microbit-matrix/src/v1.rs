@@ -0,0 +1,47 @@
+//! Display layout of the micro:bit v1, where the 25 LEDs are wired as a 3x9 matrix.
+
+/// Number of hardware rows.
+pub const ROWS: usize = 3;
+
+/// Number of hardware columns.
+pub const COLS: usize = 9;
+
+/// Maps each pixel, indexed by row and then column, to its hardware row and column.
+pub(crate) const COORDINATES: [[(usize, usize); 5]; 5] = [
+    [(0, 0), (1, 3), (0, 1), (1, 4), (0, 2)],
+    [(2, 3), (2, 4), (2, 5), (2, 6), (2, 7)],
+    [(1, 1), (0, 8), (1, 2), (2, 8), (1, 0)],
+    [(0, 7), (0, 6), (0, 5), (0, 4), (0, 3)],
+    [(2, 2), (1, 6), (2, 0), (1, 5), (2, 1)],
+];
+
+#[cfg(feature = "v1")]
+mod hw {
+    use hal::gpio::{Level, Output, Pin, PushPull};
+    use nrf51_hal as hal;
+
+    use crate::LedMatrix;
+
+    impl LedMatrix<Pin<Output<PushPull>>> {
+        pub fn new(ports: hal::gpio::p0::Parts) -> Self {
+            Self::from_pins(
+                [
+                    ports.p0_13.into_push_pull_output(Level::Low).degrade(),
+                    ports.p0_14.into_push_pull_output(Level::Low).degrade(),
+                    ports.p0_15.into_push_pull_output(Level::Low).degrade(),
+                ],
+                [
+                    ports.p0_04.into_push_pull_output(Level::Low).degrade(),
+                    ports.p0_05.into_push_pull_output(Level::Low).degrade(),
+                    ports.p0_06.into_push_pull_output(Level::Low).degrade(),
+                    ports.p0_07.into_push_pull_output(Level::Low).degrade(),
+                    ports.p0_08.into_push_pull_output(Level::Low).degrade(),
+                    ports.p0_09.into_push_pull_output(Level::Low).degrade(),
+                    ports.p0_10.into_push_pull_output(Level::Low).degrade(),
+                    ports.p0_11.into_push_pull_output(Level::Low).degrade(),
+                    ports.p0_12.into_push_pull_output(Level::Low).degrade(),
+                ],
+            )
+        }
+    }
+}
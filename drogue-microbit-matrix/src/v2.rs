@@ -0,0 +1,75 @@
+//! Display layout of the micro:bit v2, where the LEDs are wired as a 5x5 matrix.
+
+/// Number of hardware rows.
+pub const ROWS: usize = 5;
+
+/// Number of hardware columns.
+pub const COLS: usize = 5;
+
+/// Maps each pixel, indexed by row and then column, to its hardware row and column.
+pub(crate) const COORDINATES: [[(usize, usize); 5]; 5] = [
+    [(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)],
+    [(1, 0), (1, 1), (1, 2), (1, 3), (1, 4)],
+    [(2, 0), (2, 1), (2, 2), (2, 3), (2, 4)],
+    [(3, 0), (3, 1), (3, 2), (3, 3), (3, 4)],
+    [(4, 0), (4, 1), (4, 2), (4, 3), (4, 4)],
+];
+
+#[cfg(feature = "v2")]
+mod hw {
+    use hal::gpio::{Level, Output, Pin, PushPull};
+    use nrf52833_hal as hal;
+
+    use crate::LedMatrix;
+
+    impl LedMatrix<Pin<Output<PushPull>>> {
+        pub fn new(p0: hal::gpio::p0::Parts, p1: hal::gpio::p1::Parts) -> Self {
+            Self::from_pins(
+                [
+                    p0.p0_21.into_push_pull_output(Level::Low).degrade(),
+                    p0.p0_22.into_push_pull_output(Level::Low).degrade(),
+                    p0.p0_15.into_push_pull_output(Level::Low).degrade(),
+                    p0.p0_24.into_push_pull_output(Level::Low).degrade(),
+                    p0.p0_19.into_push_pull_output(Level::Low).degrade(),
+                ],
+                [
+                    p0.p0_28.into_push_pull_output(Level::Low).degrade(),
+                    p0.p0_11.into_push_pull_output(Level::Low).degrade(),
+                    p0.p0_31.into_push_pull_output(Level::Low).degrade(),
+                    p1.p1_05.into_push_pull_output(Level::Low).degrade(),
+                    p0.p0_30.into_push_pull_output(Level::Low).degrade(),
+                ],
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::{MockPin, PinRecorder};
+    use crate::{LedMatrix, MAX_BRIGHTNESS};
+
+    #[test]
+    fn each_row_drives_its_own_line() {
+        let recorder = PinRecorder::new();
+        let (rows, cols) = recorder.pins();
+        let mut m: LedMatrix<MockPin> = LedMatrix::from_pins(rows, cols);
+        for y in 0..5 {
+            m.set_brightness(y, y, MAX_BRIGHTNESS);
+        }
+        m.set_brightness(4, 0, MAX_BRIGHTNESS);
+
+        for row in (1..=5).map(|r| r % 5) {
+            m.refresh();
+            assert_eq!(recorder.lit_rows(), [row]);
+            if row == 0 {
+                assert_eq!(recorder.lit_cols(), [0, 4]);
+            } else {
+                assert_eq!(recorder.lit_cols(), [row]);
+            }
+            for _ in 1..MAX_BRIGHTNESS {
+                m.refresh();
+            }
+        }
+    }
+}
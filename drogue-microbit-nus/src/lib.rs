@@ -0,0 +1,256 @@
+#![no_std]
+
+use core::fmt;
+use drogue_microbit_gatt::{impl_provider, AttributeId, GattTable, Properties, MAX_VALUE_LEN};
+use heapless::spsc::Queue;
+use heapless::{consts, ArrayLength};
+use rubble::att::{AttributeProvider, Handle};
+use rubble::uuid::Uuid128;
+use rubble::Error;
+
+pub use drogue_microbit_gatt::Notification;
+
+/// A UUID of the Nordic UART Service, which are all of the form
+/// `6E40xxxx-B5A3-F393-E0A9-E50E24DCCA9E`.
+const fn nus_uuid(short: u16) -> Uuid128 {
+    Uuid128::from_bytes([
+        0x6E,
+        0x40,
+        (short >> 8) as u8,
+        short as u8,
+        0xB5,
+        0xA3,
+        0xF3,
+        0x93,
+        0xE0,
+        0xA9,
+        0xE5,
+        0x0E,
+        0x24,
+        0xDC,
+        0xCA,
+        0x9E,
+    ])
+}
+
+pub const NUS_UUID: Uuid128 = nus_uuid(0x0001);
+const RX_UUID: Uuid128 = nus_uuid(0x0002);
+const TX_UUID: Uuid128 = nus_uuid(0x0003);
+
+/// An `AttributeProvider` that will enumerate as a Nordic UART Service, giving a byte stream in
+/// each direction.
+///
+/// Bytes written by the client to the RX characteristic are buffered until the application reads
+/// them, and bytes written by the application are buffered until the client enables
+/// notifications of the TX characteristic and they are sent with `take_notification`. Each buffer
+/// holds `N` bytes. When the receive buffer is full, further bytes are dropped and counted.
+///
+/// ```
+/// use core::fmt::Write;
+/// use drogue_microbit_nus::UartService;
+///
+/// let mut uart: UartService = UartService::new();
+/// writeln!(uart, "Hello").unwrap();
+///
+/// let mut buf = [0; 16];
+/// let len = uart.read(&mut buf);
+/// assert_eq!(len, 0);
+/// ```
+pub struct UartService<N: ArrayLength<u8> = consts::U64> {
+    table: GattTable<consts::U6>,
+    rx_id: AttributeId,
+    tx_id: AttributeId,
+    rx: Queue<u8, N>,
+    tx: Queue<u8, N>,
+    dropped: usize,
+}
+
+impl<N: ArrayLength<u8>> UartService<N> {
+    pub fn new() -> Self {
+        let mut table = GattTable::builder();
+        table.service(NUS_UUID).unwrap();
+        let rx_id = table
+            .characteristic(
+                RX_UUID,
+                Properties::WRITE | Properties::WRITE_WITHOUT_RESPONSE,
+                &[],
+            )
+            .unwrap();
+        let tx_id = table
+            .characteristic(TX_UUID, Properties::NOTIFY, &[])
+            .unwrap();
+        Self {
+            table: table.build(),
+            rx_id,
+            tx_id,
+            rx: Queue::new(),
+            tx: Queue::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Read received bytes into `buf`, returning the number of bytes read.
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        let mut len = 0;
+        for b in buf.iter_mut() {
+            match self.rx.dequeue() {
+                Some(byte) => *b = byte,
+                None => break,
+            }
+            len += 1;
+        }
+        len
+    }
+
+    /// Read a single received byte.
+    pub fn read_byte(&mut self) -> Option<u8> {
+        self.rx.dequeue()
+    }
+
+    /// Queue `data` for sending, returning the number of bytes that fit in the buffer.
+    pub fn write(&mut self, data: &[u8]) -> usize {
+        let mut len = 0;
+        for byte in data {
+            if self.tx.enqueue(*byte).is_err() {
+                break;
+            }
+            len += 1;
+        }
+        len
+    }
+
+    /// Returns true if there are bytes waiting to be sent.
+    pub fn has_pending_tx(&self) -> bool {
+        !self.tx.is_empty()
+    }
+
+    /// Returns true if the client has enabled notifications, so that written bytes are sent.
+    pub fn is_connected(&self) -> bool {
+        self.table.notifications_enabled(self.tx_id)
+    }
+
+    /// The number of received bytes dropped because the receive buffer was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Take the next bytes to send as a notification of the TX characteristic, if the client has
+    /// enabled notifications. Each notification carries up to `MAX_VALUE_LEN` bytes, so call this
+    /// until it returns `None` while there is room in the transmit queue of the link.
+    pub fn take_notification(&mut self) -> Option<Notification> {
+        if !self.is_connected() || self.tx.is_empty() {
+            return None;
+        }
+        let mut data = [0; MAX_VALUE_LEN];
+        let mut len = 0;
+        while len < data.len() {
+            match self.tx.dequeue() {
+                Some(byte) => data[len] = byte,
+                None => break,
+            }
+            len += 1;
+        }
+        self.table.set_value(self.tx_id, &data[..len]).unwrap();
+        Some(self.table.notification(self.tx_id))
+    }
+
+    /// Writes to RX are queued for `read`, other writes go to the table.
+    fn write_attribute(&mut self, handle: Handle, data: &[u8]) -> Result<(), Error> {
+        if self.table.id(handle) == Some(self.rx_id) {
+            for byte in data {
+                if self.rx.enqueue(*byte).is_err() {
+                    self.dropped += 1;
+                }
+            }
+            Ok(())
+        } else {
+            self.table.write_attr(handle, data)
+        }
+    }
+}
+
+impl<N: ArrayLength<u8>> Default for UartService<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: ArrayLength<u8>> fmt::Write for UartService<N> {
+    /// Queue a string for sending. Fails without writing anything if it does not fit in the
+    /// transmit buffer.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.tx.capacity() - self.tx.len() < s.len() {
+            return Err(fmt::Error);
+        }
+        self.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+impl_provider!(impl<N: ArrayLength<u8>> UartService<N>, write = write_attribute);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+    use drogue_microbit_gatt::GattProvider;
+    use rubble::att::AttributeAccessPermissions;
+
+    const RX_HANDLE: u16 = 3;
+    const TX_HANDLE: u16 = 5;
+    const TX_CCCD_HANDLE: u16 = 6;
+
+    #[test]
+    fn attributes() {
+        let uart: UartService = UartService::new();
+        assert_eq!(uart.last_handle(), TX_CCCD_HANDLE);
+        assert_eq!(
+            uart.attr_access_permissions(Handle::from_raw(RX_HANDLE)),
+            AttributeAccessPermissions::Writeable
+        );
+        assert_eq!(
+            uart.attr_access_permissions(Handle::from_raw(TX_HANDLE)),
+            AttributeAccessPermissions::Readable
+        );
+    }
+
+    #[test]
+    fn receive() {
+        let mut uart: UartService<consts::U8> = UartService::new();
+        uart.write_attr(Handle::from_raw(RX_HANDLE), b"hello")
+            .unwrap();
+        uart.write_attr(Handle::from_raw(RX_HANDLE), b" world")
+            .unwrap();
+        assert_eq!(uart.dropped(), 3);
+
+        let mut buf = [0; 4];
+        assert_eq!(uart.read(&mut buf), 4);
+        assert_eq!(&buf, b"hell");
+        assert_eq!(uart.read_byte(), Some(b'o'));
+        assert_eq!(uart.read(&mut buf), 3);
+        assert_eq!(&buf[..3], b" wo");
+        assert_eq!(uart.read_byte(), None);
+    }
+
+    #[test]
+    fn transmit() {
+        let mut uart: UartService<consts::U32> = UartService::new();
+        write!(uart, "The quick brown fox {}", 42).unwrap();
+        assert!(uart.has_pending_tx());
+        assert_eq!(uart.take_notification(), None);
+
+        uart.write_attr(Handle::from_raw(TX_CCCD_HANDLE), &[0x01, 0x00])
+            .unwrap();
+        assert!(uart.is_connected());
+        let notification = uart.take_notification().unwrap();
+        assert_eq!(notification.handle(), Handle::from_raw(TX_HANDLE));
+        assert_eq!(notification.value(), b"The quick brown fox ");
+        let notification = uart.take_notification().unwrap();
+        assert_eq!(notification.value(), b"42");
+        assert_eq!(uart.take_notification(), None);
+        assert!(!uart.has_pending_tx());
+
+        assert_eq!(uart.write(&[0; 40]), 32);
+        assert!(write!(uart, "x").is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{check_period, microbit_uuid, period};
+use drogue_microbit_gatt::{impl_provider, AttributeId, GattTable, Notification, Properties};
+use heapless::consts;
+use rubble::att::{AttributeProvider, Handle};
+use rubble::uuid::Uuid128;
+use rubble::Error;
+
+pub const ACCELEROMETER_SERVICE_UUID: Uuid128 = microbit_uuid(0x0753);
+const ACCELEROMETER_DATA_UUID: Uuid128 = microbit_uuid(0xCA4B);
+const ACCELEROMETER_PERIOD_UUID: Uuid128 = microbit_uuid(0xFB24);
+
+/// Sampling periods supported by the micro:bit runtime, in milliseconds.
+const PERIODS: [u16; 8] = [1, 2, 5, 10, 20, 80, 160, 640];
+const DEFAULT_PERIOD: u16 = 20;
+
+/// Acceleration along each axis in milli-g.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Acceleration {
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+}
+
+impl Acceleration {
+    pub fn new(x: i16, y: i16, z: i16) -> Self {
+        Self { x, y, z }
+    }
+
+    fn encode(&self) -> [u8; 6] {
+        let mut data = [0; 6];
+        data[0..2].copy_from_slice(&self.x.to_le_bytes());
+        data[2..4].copy_from_slice(&self.y.to_le_bytes());
+        data[4..6].copy_from_slice(&self.z.to_le_bytes());
+        data
+    }
+
+    fn decode(data: &[u8]) -> Self {
+        Self {
+            x: i16::from_le_bytes([data[0], data[1]]),
+            y: i16::from_le_bytes([data[2], data[3]]),
+            z: i16::from_le_bytes([data[4], data[5]]),
+        }
+    }
+}
+
+/// The accelerometer service, which reports the acceleration of the micro:bit.
+///
+/// Clients can change how often the accelerometer is sampled, so the application should read
+/// `period` each time it schedules a measurement.
+pub struct AccelerometerService {
+    table: GattTable<consts::U6>,
+    data_id: AttributeId,
+    period_id: AttributeId,
+    notification_pending: bool,
+}
+
+impl AccelerometerService {
+    pub fn new() -> Self {
+        let mut table = GattTable::builder();
+        table.service(ACCELEROMETER_SERVICE_UUID).unwrap();
+        let data_id = table
+            .characteristic(
+                ACCELEROMETER_DATA_UUID,
+                Properties::READ | Properties::NOTIFY,
+                &Acceleration::default().encode(),
+            )
+            .unwrap();
+        let period_id = table
+            .characteristic(
+                ACCELEROMETER_PERIOD_UUID,
+                Properties::READ | Properties::WRITE,
+                &DEFAULT_PERIOD.to_le_bytes(),
+            )
+            .unwrap();
+        Self {
+            table: table.build(),
+            data_id,
+            period_id,
+            notification_pending: false,
+        }
+    }
+
+    pub fn acceleration(&self) -> Acceleration {
+        Acceleration::decode(self.table.value(self.data_id))
+    }
+
+    /// Update the acceleration. If it changed and the client has enabled notifications, a
+    /// notification is queued which can be retrieved with `take_notification`.
+    pub fn set_acceleration(&mut self, acceleration: Acceleration) {
+        if acceleration != self.acceleration() {
+            self.table
+                .set_value(self.data_id, &acceleration.encode())
+                .unwrap();
+            self.notification_pending = self.table.notifications_enabled(self.data_id);
+        }
+    }
+
+    /// The sampling period in milliseconds.
+    pub fn period(&self) -> u16 {
+        period(&self.table, self.period_id)
+    }
+
+    /// Take the notification queued by a change of the acceleration, if any.
+    pub fn take_notification(&mut self) -> Option<Notification> {
+        if self.notification_pending {
+            self.notification_pending = false;
+            Some(self.table.notification(self.data_id))
+        } else {
+            None
+        }
+    }
+
+    fn write(&mut self, handle: Handle, data: &[u8]) -> Result<(), Error> {
+        if self.table.id(handle) == Some(self.period_id) {
+            check_period(data)?;
+            // Like the micro:bit runtime, round up to the next supported period
+            let requested = u16::from_le_bytes([data[0], data[1]]);
+            let period = PERIODS
+                .iter()
+                .copied()
+                .find(|p| *p >= requested)
+                .unwrap_or(PERIODS[PERIODS.len() - 1]);
+            return self.table.write_attr(handle, &period.to_le_bytes());
+        }
+        self.table.write_attr(handle, data)?;
+        self.notification_pending &= self.table.notifications_enabled(self.data_id);
+        Ok(())
+    }
+}
+
+impl Default for AccelerometerService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_provider!(AccelerometerService, write = write);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::read;
+
+    const DATA_HANDLE: u16 = 3;
+    const CCCD_HANDLE: u16 = 4;
+    const PERIOD_HANDLE: u16 = 6;
+
+    #[test]
+    fn acceleration() {
+        let mut service = AccelerometerService::new();
+        service
+            .write_attr(Handle::from_raw(CCCD_HANDLE), &[0x01, 0x00])
+            .unwrap();
+        service.set_acceleration(Acceleration::new(-1, 1000, 256));
+        assert_eq!(
+            &read(&mut service, DATA_HANDLE)[..],
+            &[0xFF, 0xFF, 0xE8, 0x03, 0x00, 0x01]
+        );
+        let notification = service.take_notification().unwrap();
+        assert_eq!(notification.handle(), Handle::from_raw(DATA_HANDLE));
+        service.set_acceleration(Acceleration::new(-1, 1000, 256));
+        assert_eq!(service.take_notification(), None);
+    }
+
+    #[test]
+    fn period() {
+        let mut service = AccelerometerService::new();
+        assert_eq!(service.period(), 20);
+        service
+            .write_attr(Handle::from_raw(PERIOD_HANDLE), &[80, 0])
+            .unwrap();
+        assert_eq!(service.period(), 80);
+        service
+            .write_attr(Handle::from_raw(PERIOD_HANDLE), &[100, 0])
+            .unwrap();
+        assert_eq!(service.period(), 160);
+        service
+            .write_attr(Handle::from_raw(PERIOD_HANDLE), &[0xE8, 0x03])
+            .unwrap();
+        assert_eq!(service.period(), 640);
+        assert_eq!(&read(&mut service, PERIOD_HANDLE)[..], &[0x80, 0x02]);
+        assert_eq!(
+            service.write_attr(Handle::from_raw(PERIOD_HANDLE), &[0, 0]),
+            Err(Error::InvalidValue)
+        );
+    }
+}
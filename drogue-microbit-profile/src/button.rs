@@ -0,0 +1,142 @@
+use crate::microbit_uuid;
+use drogue_microbit_gatt::{impl_provider, AttributeId, GattTable, Notification, Properties};
+use heapless::consts;
+use rubble::att::{AttributeProvider, Handle};
+use rubble::uuid::Uuid128;
+use rubble::Error;
+
+pub const BUTTON_SERVICE_UUID: Uuid128 = microbit_uuid(0x9882);
+const BUTTON_A_STATE_UUID: Uuid128 = microbit_uuid(0xDA90);
+const BUTTON_B_STATE_UUID: Uuid128 = microbit_uuid(0xDA91);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    A,
+    B,
+}
+
+/// The state of a button, as reported by the Button State characteristics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonState {
+    Released = 0,
+    Pressed = 1,
+    LongPressed = 2,
+}
+
+/// The button service, which notifies clients when button A or B is pressed and released.
+pub struct ButtonService {
+    table: GattTable<consts::U7>,
+    ids: [AttributeId; 2],
+    pending: [bool; 2],
+}
+
+impl ButtonService {
+    pub fn new() -> Self {
+        let mut table = GattTable::builder();
+        table.service(BUTTON_SERVICE_UUID).unwrap();
+        let released = [ButtonState::Released as u8];
+        let properties = Properties::READ | Properties::NOTIFY;
+        let ids = [
+            table
+                .characteristic(BUTTON_A_STATE_UUID, properties, &released)
+                .unwrap(),
+            table
+                .characteristic(BUTTON_B_STATE_UUID, properties, &released)
+                .unwrap(),
+        ];
+        Self {
+            table: table.build(),
+            ids,
+            pending: [false; 2],
+        }
+    }
+
+    pub fn state(&self, button: Button) -> ButtonState {
+        match self.table.value(self.ids[button as usize])[0] {
+            1 => ButtonState::Pressed,
+            2 => ButtonState::LongPressed,
+            _ => ButtonState::Released,
+        }
+    }
+
+    /// Update the state of a button. If it changed and the client has enabled notifications, a
+    /// notification is queued which can be retrieved with `take_notification`.
+    pub fn set_state(&mut self, button: Button, state: ButtonState) {
+        if state != self.state(button) {
+            let id = self.ids[button as usize];
+            self.table.set_value(id, &[state as u8]).unwrap();
+            self.pending[button as usize] = self.table.notifications_enabled(id);
+        }
+    }
+
+    /// Take a queued notification of a button state, if any.
+    pub fn take_notification(&mut self) -> Option<Notification> {
+        let index = self.pending.iter().position(|pending| *pending)?;
+        self.pending[index] = false;
+        Some(self.table.notification(self.ids[index]))
+    }
+
+    fn write(&mut self, handle: Handle, data: &[u8]) -> Result<(), Error> {
+        self.table.write_attr(handle, data)?;
+        for (pending, id) in self.pending.iter_mut().zip(self.ids.iter()) {
+            *pending &= self.table.notifications_enabled(*id);
+        }
+        Ok(())
+    }
+}
+
+impl Default for ButtonService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_provider!(ButtonService, write = write);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::read;
+
+    const BUTTON_A_HANDLE: u16 = 3;
+    const BUTTON_A_CCCD_HANDLE: u16 = 4;
+    const BUTTON_B_HANDLE: u16 = 6;
+    const BUTTON_B_CCCD_HANDLE: u16 = 7;
+
+    #[test]
+    fn notifications() {
+        let mut buttons = ButtonService::new();
+        buttons.set_state(Button::A, ButtonState::Pressed);
+        assert_eq!(buttons.state(Button::A), ButtonState::Pressed);
+        assert_eq!(&read(&mut buttons, BUTTON_A_HANDLE)[..], &[1]);
+        assert_eq!(buttons.take_notification(), None);
+
+        for handle in [BUTTON_A_CCCD_HANDLE, BUTTON_B_CCCD_HANDLE].iter() {
+            buttons
+                .write_attr(Handle::from_raw(*handle), &[0x01, 0x00])
+                .unwrap();
+        }
+        buttons.set_state(Button::B, ButtonState::LongPressed);
+        buttons.set_state(Button::A, ButtonState::Released);
+        // Unchanged states are not notified
+        buttons.set_state(Button::A, ButtonState::Released);
+
+        let notification = buttons.take_notification().unwrap();
+        assert_eq!(notification.handle(), Handle::from_raw(BUTTON_A_HANDLE));
+        assert_eq!(notification.value(), &[0]);
+        let notification = buttons.take_notification().unwrap();
+        assert_eq!(notification.handle(), Handle::from_raw(BUTTON_B_HANDLE));
+        assert_eq!(notification.value(), &[2]);
+        assert_eq!(buttons.take_notification(), None);
+
+        buttons.set_state(Button::B, ButtonState::Released);
+        buttons
+            .write_attr(Handle::from_raw(BUTTON_B_CCCD_HANDLE), &[0x00, 0x00])
+            .unwrap();
+        assert_eq!(buttons.take_notification(), None);
+        assert_eq!(
+            buttons.write_attr(Handle::from_raw(BUTTON_A_HANDLE), &[1]),
+            Err(Error::InvalidValue)
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{check_period, microbit_uuid, period};
+use drogue_microbit_gatt::{impl_provider, AttributeId, GattTable, Properties};
+use drogue_microbit_matrix::{Image, MAX_BRIGHTNESS};
+use heapless::{consts, String};
+use rubble::att::{AttributeProvider, Handle};
+use rubble::uuid::Uuid128;
+use rubble::Error;
+
+pub const LED_SERVICE_UUID: Uuid128 = microbit_uuid(0xD91D);
+const MATRIX_STATE_UUID: Uuid128 = microbit_uuid(0x7B77);
+const TEXT_UUID: Uuid128 = microbit_uuid(0x93EE);
+const SCROLLING_DELAY_UUID: Uuid128 = microbit_uuid(0x0D2D);
+
+/// Scrolling delay of the micro:bit runtime, in milliseconds per column.
+const DEFAULT_SCROLLING_DELAY: u16 = 120;
+
+/// A change of the display requested by a client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedCommand {
+    /// Show an image, written to the LED Matrix State.
+    Show(Image),
+    /// Scroll text across the display, moving one column every `delay_ms` milliseconds.
+    Scroll {
+        text: String<consts::U20>,
+        delay_ms: u16,
+    },
+}
+
+/// The LED service, which lets clients show images and scroll text on the display.
+///
+/// Writes are not applied to the display directly, since the `LedMatrix` is usually owned by
+/// another task. Instead they are queued as a `LedCommand`, and the application should call
+/// `set_image` with what the display shows so that clients read the current state.
+pub struct LedService {
+    table: GattTable<consts::U7>,
+    state_id: AttributeId,
+    text_id: AttributeId,
+    delay_id: AttributeId,
+    command: Option<LedCommand>,
+}
+
+impl LedService {
+    pub fn new() -> Self {
+        let mut table = GattTable::builder();
+        table.service(LED_SERVICE_UUID).unwrap();
+        let state_id = table
+            .characteristic(
+                MATRIX_STATE_UUID,
+                Properties::READ | Properties::WRITE,
+                &[0; 5],
+            )
+            .unwrap();
+        let text_id = table
+            .characteristic(TEXT_UUID, Properties::WRITE, &[])
+            .unwrap();
+        let delay_id = table
+            .characteristic(
+                SCROLLING_DELAY_UUID,
+                Properties::READ | Properties::WRITE,
+                &DEFAULT_SCROLLING_DELAY.to_le_bytes(),
+            )
+            .unwrap();
+        Self {
+            table: table.build(),
+            state_id,
+            text_id,
+            delay_id,
+            command: None,
+        }
+    }
+
+    /// Update the LED Matrix State read by clients. Pixels are either on or off, so any brightness
+    /// above 0 is reported as on.
+    pub fn set_image(&mut self, image: &Image) {
+        self.table
+            .set_value(self.state_id, &encode_image(image))
+            .unwrap();
+    }
+
+    /// The image of the LED Matrix State, with lit pixels at full brightness.
+    pub fn image(&self) -> Image {
+        let mut state = [0; 5];
+        state.copy_from_slice(self.table.value(self.state_id));
+        decode_image(&state)
+    }
+
+    /// The delay in milliseconds between each column when scrolling text.
+    pub fn scrolling_delay(&self) -> u16 {
+        period(&self.table, self.delay_id)
+    }
+
+    /// Take the last change of the display requested by a client, if any.
+    pub fn take_command(&mut self) -> Option<LedCommand> {
+        self.command.take()
+    }
+
+    fn write(&mut self, handle: Handle, data: &[u8]) -> Result<(), Error> {
+        let id = self.table.id(handle).ok_or(Error::InvalidValue)?;
+        if id == self.state_id {
+            if data.len() != 5 {
+                return Err(Error::InvalidLength);
+            }
+            let mut state = [0; 5];
+            state.copy_from_slice(data);
+            self.table.write_attr(handle, data)?;
+            self.command = Some(LedCommand::Show(decode_image(&state)));
+        } else if id == self.text_id {
+            let text = core::str::from_utf8(data).map_err(|_| Error::InvalidValue)?;
+            // The table rejects values longer than the 20 bytes the string can hold
+            self.table.write_attr(handle, data)?;
+            self.command = Some(LedCommand::Scroll {
+                text: String::from(text),
+                delay_ms: self.scrolling_delay(),
+            });
+        } else if id == self.delay_id {
+            check_period(data)?;
+            self.table.write_attr(handle, data)?;
+        } else {
+            self.table.write_attr(handle, data)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for LedService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_provider!(LedService, write = write);
+
+/// Encode an image as the LED Matrix State, with one octet per row where bit 4 is the leftmost
+/// pixel.
+fn encode_image(image: &Image) -> [u8; 5] {
+    let mut state = [0; 5];
+    for (row, pixels) in state.iter_mut().zip(image.pixels().iter()) {
+        for (x, &brightness) in pixels.iter().enumerate() {
+            if brightness > 0 {
+                *row |= 1 << (4 - x);
+            }
+        }
+    }
+    state
+}
+
+fn decode_image(state: &[u8; 5]) -> Image {
+    let mut image = Image::blank();
+    for (y, row) in state.iter().enumerate() {
+        for x in 0..5 {
+            if row & (1 << (4 - x)) != 0 {
+                image.set_pixel(x, y, MAX_BRIGHTNESS);
+            }
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::read;
+
+    const STATE_HANDLE: u16 = 3;
+    const TEXT_HANDLE: u16 = 5;
+    const DELAY_HANDLE: u16 = 7;
+
+    #[test]
+    fn matrix_state() {
+        let heart: Image = "09090:99999:99999:09990:00900".parse().unwrap();
+        assert_eq!(
+            encode_image(&heart),
+            [0b01010, 0b11111, 0b11111, 0b01110, 0b00100]
+        );
+        assert_eq!(decode_image(&encode_image(&heart)), heart);
+
+        let dim: Image = "10000:00000:00000:00000:00001".parse().unwrap();
+        assert_eq!(encode_image(&dim), [0b10000, 0, 0, 0, 0b00001]);
+
+        let mut led = LedService::new();
+        led.set_image(&heart);
+        assert_eq!(
+            &read(&mut led, STATE_HANDLE)[..],
+            &[0b01010, 0b11111, 0b11111, 0b01110, 0b00100]
+        );
+        assert_eq!(led.image(), heart);
+    }
+
+    #[test]
+    fn commands() {
+        let mut led = LedService::new();
+        assert_eq!(led.take_command(), None);
+
+        led.write_attr(Handle::from_raw(STATE_HANDLE), &[0b10001, 0, 0, 0, 0])
+            .unwrap();
+        let mut image = Image::blank();
+        image.set_pixel(0, 0, MAX_BRIGHTNESS);
+        image.set_pixel(4, 0, MAX_BRIGHTNESS);
+        assert_eq!(led.take_command(), Some(LedCommand::Show(image)));
+        assert_eq!(led.take_command(), None);
+        assert_eq!(
+            led.write_attr(Handle::from_raw(STATE_HANDLE), &[0; 4]),
+            Err(Error::InvalidLength)
+        );
+
+        led.write_attr(Handle::from_raw(DELAY_HANDLE), &[200, 0])
+            .unwrap();
+        assert_eq!(led.scrolling_delay(), 200);
+        assert_eq!(&read(&mut led, DELAY_HANDLE)[..], &[200, 0]);
+        assert_eq!(
+            led.write_attr(Handle::from_raw(DELAY_HANDLE), &[0, 0]),
+            Err(Error::InvalidValue)
+        );
+
+        led.write_attr(Handle::from_raw(TEXT_HANDLE), b"Hello")
+            .unwrap();
+        assert_eq!(
+            led.take_command(),
+            Some(LedCommand::Scroll {
+                text: String::from("Hello"),
+                delay_ms: 200,
+            })
+        );
+        assert_eq!(
+            led.write_attr(Handle::from_raw(TEXT_HANDLE), &[0xFF]),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(led.take_command(), None);
+    }
+}
@@ -0,0 +1,101 @@
+//! Services of the micro:bit Bluetooth profile, as implemented by the official micro:bit runtime,
+//! so that existing micro:bit apps can talk to drogue firmware.
+//!
+//! Each service is a separate `GattProvider`, so an application can chain the ones it supports.
+//! Values written by clients are queued for the application to act on, and changes made by the
+//! application are notified to subscribed clients with `take_notification`.
+#![no_std]
+
+use drogue_microbit_gatt::{AttributeId, Entry, GattTable};
+use heapless::ArrayLength;
+use rubble::uuid::Uuid128;
+use rubble::Error;
+
+mod accelerometer;
+mod button;
+mod led;
+mod temperature;
+
+pub use accelerometer::{Acceleration, AccelerometerService, ACCELEROMETER_SERVICE_UUID};
+pub use button::{Button, ButtonService, ButtonState, BUTTON_SERVICE_UUID};
+pub use drogue_microbit_gatt::Notification;
+pub use led::{LedCommand, LedService, LED_SERVICE_UUID};
+pub use temperature::{TemperatureService, TEMPERATURE_SERVICE_UUID};
+
+/// A UUID of the micro:bit profile, which are all of the form
+/// `E95Dxxxx-251D-470A-A062-FA1922DFA9A8`.
+pub const fn microbit_uuid(short: u16) -> Uuid128 {
+    Uuid128::from_bytes([
+        0xE9,
+        0x5D,
+        (short >> 8) as u8,
+        short as u8,
+        0x25,
+        0x1D,
+        0x47,
+        0x0A,
+        0xA0,
+        0x62,
+        0xFA,
+        0x19,
+        0x22,
+        0xDF,
+        0xA9,
+        0xA8,
+    ])
+}
+
+/// The period in milliseconds of a sampling period characteristic.
+fn period<N: ArrayLength<Entry>>(table: &GattTable<N>, id: AttributeId) -> u16 {
+    let value = table.value(id);
+    u16::from_le_bytes([value[0], value[1]])
+}
+
+/// Validate a write to a sampling period characteristic, which must be a non-zero uint16.
+fn check_period(data: &[u8]) -> Result<(), Error> {
+    match data {
+        [0, 0] => Err(Error::InvalidValue),
+        [_, _] => Ok(()),
+        _ => Err(Error::InvalidLength),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rubble::att::{AttributeProvider, Handle, HandleRange};
+
+    /// Read the value of the attribute at `handle`.
+    pub fn read<P: AttributeProvider>(
+        provider: &mut P,
+        handle: u16,
+    ) -> heapless::Vec<u8, heapless::consts::U20> {
+        let mut value = heapless::Vec::new();
+        let handle = Handle::from_raw(handle);
+        provider
+            .for_attrs_in_range(HandleRange::new(handle, handle), |_, attr| {
+                value.extend_from_slice(attr.value.as_slice()).unwrap();
+                Ok(())
+            })
+            .unwrap();
+        value
+    }
+
+    #[test]
+    fn uuids() {
+        assert_eq!(
+            microbit_uuid(0xD91D),
+            Uuid128::from_bytes([
+                0xE9, 0x5D, 0xD9, 0x1D, 0x25, 0x1D, 0x47, 0x0A, 0xA0, 0x62, 0xFA, 0x19, 0x22, 0xDF,
+                0xA9, 0xA8,
+            ])
+        );
+    }
+
+    #[test]
+    fn periods() {
+        assert_eq!(check_period(&[20, 0]), Ok(()));
+        assert_eq!(check_period(&[0, 0]), Err(Error::InvalidValue));
+        assert_eq!(check_period(&[20]), Err(Error::InvalidLength));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{check_period, microbit_uuid, period};
+use drogue_microbit_gatt::{impl_provider, AttributeId, GattTable, Notification, Properties};
+use heapless::consts;
+use rubble::att::{AttributeProvider, Handle};
+use rubble::uuid::Uuid128;
+use rubble::Error;
+
+pub const TEMPERATURE_SERVICE_UUID: Uuid128 = microbit_uuid(0x6100);
+const TEMPERATURE_UUID: Uuid128 = microbit_uuid(0x9250);
+const TEMPERATURE_PERIOD_UUID: Uuid128 = microbit_uuid(0x1B25);
+
+/// Sampling period of the micro:bit runtime, in milliseconds.
+const DEFAULT_PERIOD: u16 = 1000;
+
+/// The temperature service, which reports the temperature in whole degrees Celsius.
+///
+/// Clients can change how often the temperature is sampled, so the application should read
+/// `period` each time it schedules a measurement.
+pub struct TemperatureService {
+    table: GattTable<consts::U6>,
+    temperature_id: AttributeId,
+    period_id: AttributeId,
+    notification_pending: bool,
+}
+
+impl TemperatureService {
+    pub fn new() -> Self {
+        let mut table = GattTable::builder();
+        table.service(TEMPERATURE_SERVICE_UUID).unwrap();
+        let temperature_id = table
+            .characteristic(
+                TEMPERATURE_UUID,
+                Properties::READ | Properties::NOTIFY,
+                &[0],
+            )
+            .unwrap();
+        let period_id = table
+            .characteristic(
+                TEMPERATURE_PERIOD_UUID,
+                Properties::READ | Properties::WRITE,
+                &DEFAULT_PERIOD.to_le_bytes(),
+            )
+            .unwrap();
+        Self {
+            table: table.build(),
+            temperature_id,
+            period_id,
+            notification_pending: false,
+        }
+    }
+
+    /// The temperature in degrees Celsius.
+    pub fn temperature(&self) -> i8 {
+        self.table.value(self.temperature_id)[0] as i8
+    }
+
+    /// Update the temperature. If it changed and the client has enabled notifications, a
+    /// notification is queued which can be retrieved with `take_notification`.
+    pub fn set_temperature(&mut self, celsius: i8) {
+        if celsius != self.temperature() {
+            self.table
+                .set_value(self.temperature_id, &celsius.to_le_bytes())
+                .unwrap();
+            self.notification_pending = self.table.notifications_enabled(self.temperature_id);
+        }
+    }
+
+    /// Update the temperature from fractional degrees Celsius, rounding to the nearest degree.
+    pub fn set_temperature_celsius(&mut self, celsius: f32) {
+        let rounded = if celsius >= 0.0 {
+            celsius + 0.5
+        } else {
+            celsius - 0.5
+        };
+        // Float to integer casts saturate
+        self.set_temperature(rounded as i8);
+    }
+
+    /// The sampling period in milliseconds.
+    pub fn period(&self) -> u16 {
+        period(&self.table, self.period_id)
+    }
+
+    /// Take the notification queued by a change of the temperature, if any.
+    pub fn take_notification(&mut self) -> Option<Notification> {
+        if self.notification_pending {
+            self.notification_pending = false;
+            Some(self.table.notification(self.temperature_id))
+        } else {
+            None
+        }
+    }
+
+    fn write(&mut self, handle: Handle, data: &[u8]) -> Result<(), Error> {
+        if self.table.id(handle) == Some(self.period_id) {
+            check_period(data)?;
+        }
+        self.table.write_attr(handle, data)?;
+        self.notification_pending &= self.table.notifications_enabled(self.temperature_id);
+        Ok(())
+    }
+}
+
+impl Default for TemperatureService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_provider!(TemperatureService, write = write);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::read;
+
+    const TEMPERATURE_HANDLE: u16 = 3;
+    const CCCD_HANDLE: u16 = 4;
+    const PERIOD_HANDLE: u16 = 6;
+
+    #[test]
+    fn temperature() {
+        let mut service = TemperatureService::new();
+        service.set_temperature_celsius(21.5);
+        assert_eq!(service.temperature(), 22);
+        service.set_temperature_celsius(-3.25);
+        assert_eq!(service.temperature(), -3);
+        assert_eq!(&read(&mut service, TEMPERATURE_HANDLE)[..], &[0xFD]);
+        service.set_temperature_celsius(300.0);
+        assert_eq!(service.temperature(), 127);
+        assert_eq!(service.take_notification(), None);
+
+        service
+            .write_attr(Handle::from_raw(CCCD_HANDLE), &[0x01, 0x00])
+            .unwrap();
+        service.set_temperature(20);
+        let notification = service.take_notification().unwrap();
+        assert_eq!(notification.handle(), Handle::from_raw(TEMPERATURE_HANDLE));
+        assert_eq!(notification.value(), &[20]);
+        service.set_temperature(20);
+        assert_eq!(service.take_notification(), None);
+    }
+
+    #[test]
+    fn period() {
+        let mut service = TemperatureService::new();
+        assert_eq!(service.period(), 1000);
+        service
+            .write_attr(Handle::from_raw(PERIOD_HANDLE), &[0x88, 0x13])
+            .unwrap();
+        assert_eq!(service.period(), 5000);
+        assert_eq!(
+            service.write_attr(Handle::from_raw(PERIOD_HANDLE), &[0x00, 0x00]),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            service.write_attr(Handle::from_raw(TEMPERATURE_HANDLE), &[0]),
+            Err(Error::InvalidValue)
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use crate::Error;
+
+/// Maximum length of a frame after the length byte, which is the MAXLEN of the radio.
+pub const MAX_FRAME_LEN: usize = 32;
+
+/// Length of the header after the length byte.
+const HEADER_LEN: usize = 3;
+
+/// Maximum length of the payload of a frame.
+pub const MAX_PAYLOAD_LEN: usize = MAX_FRAME_LEN - HEADER_LEN;
+
+/// Version of the frame format of the micro:bit runtime.
+const VERSION: u8 = 1;
+
+/// Protocol of frames carrying datagrams, which is the only one used by MakeCode.
+const PROTOCOL_DATAGRAM: u8 = 1;
+
+/// A frame as it is transferred by the radio: a length byte, the version, group and protocol, and
+/// the payload.
+pub type FrameBuffer = [u8; 1 + MAX_FRAME_LEN];
+
+/// Write a frame carrying `payload` to `group`.
+pub fn encode_frame(buffer: &mut FrameBuffer, group: u8, payload: &[u8]) -> Result<(), Error> {
+    if payload.len() > MAX_PAYLOAD_LEN {
+        return Err(Error::TooLong);
+    }
+    buffer[0] = (HEADER_LEN + payload.len()) as u8;
+    buffer[1] = VERSION;
+    buffer[2] = group;
+    buffer[3] = PROTOCOL_DATAGRAM;
+    buffer[4..4 + payload.len()].copy_from_slice(payload);
+    Ok(())
+}
+
+/// The payload of a received frame, or `None` if it was not a datagram sent to `group`.
+pub fn decode_frame(buffer: &FrameBuffer, group: u8) -> Option<&[u8]> {
+    let len = buffer[0] as usize;
+    if !(HEADER_LEN..=MAX_FRAME_LEN).contains(&len) {
+        return None;
+    }
+    if buffer[1] != VERSION || buffer[2] != group || buffer[3] != PROTOCOL_DATAGRAM {
+        return None;
+    }
+    Some(&buffer[1 + HEADER_LEN..1 + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut buffer = [0; 1 + MAX_FRAME_LEN];
+        encode_frame(&mut buffer, 42, b"hello").unwrap();
+        assert_eq!(buffer[..9], [8, 1, 42, 1, b'h', b'e', b'l', b'l', b'o']);
+        assert_eq!(decode_frame(&buffer, 42), Some(&b"hello"[..]));
+
+        encode_frame(&mut buffer, 0, &[]).unwrap();
+        assert_eq!(decode_frame(&buffer, 0), Some(&[][..]));
+
+        // The longest MakeCode packet fills the frame
+        assert_eq!(crate::MAX_PACKET_LEN, MAX_PAYLOAD_LEN);
+        let payload = [0xAA; MAX_PAYLOAD_LEN];
+        encode_frame(&mut buffer, 7, &payload).unwrap();
+        assert_eq!(buffer[0] as usize, MAX_FRAME_LEN);
+        assert_eq!(decode_frame(&buffer, 7), Some(&payload[..]));
+        assert_eq!(
+            encode_frame(&mut buffer, 7, &[0; MAX_PAYLOAD_LEN + 1]),
+            Err(Error::TooLong)
+        );
+    }
+
+    #[test]
+    fn group_filtering() {
+        let mut buffer = [0; 1 + MAX_FRAME_LEN];
+        encode_frame(&mut buffer, 1, b"x").unwrap();
+        assert_eq!(decode_frame(&buffer, 1), Some(&b"x"[..]));
+        assert_eq!(decode_frame(&buffer, 0), None);
+        assert_eq!(decode_frame(&buffer, 255), None);
+    }
+
+    #[test]
+    fn invalid_frames() {
+        let mut buffer = [0; 1 + MAX_FRAME_LEN];
+        encode_frame(&mut buffer, 0, b"x").unwrap();
+
+        let mut version = buffer;
+        version[1] = 2;
+        assert_eq!(decode_frame(&version, 0), None);
+
+        let mut protocol = buffer;
+        protocol[3] = 0;
+        assert_eq!(decode_frame(&protocol, 0), None);
+
+        let mut short = buffer;
+        short[0] = 2;
+        assert_eq!(decode_frame(&short, 0), None);
+
+        let mut long = buffer;
+        long[0] = MAX_FRAME_LEN as u8 + 1;
+        assert_eq!(decode_frame(&long, 0), None);
+    }
+}
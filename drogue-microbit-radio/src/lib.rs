@@ -0,0 +1,43 @@
+//! The packet radio of the micro:bit runtime, for exchanging packets with other micro:bits in the
+//! same radio group, e.g. ones running MakeCode programs.
+//!
+//! The radio uses the proprietary Nordic 1 Mbit mode. Each frame carries the group it was sent to,
+//! and `frame` encodes and filters frames independently of the hardware. `Packet` is the payload
+//! sent by the MakeCode radio blocks. With the `v1` feature, `Radio` sends and receives frames with
+//! the RADIO peripheral of the micro:bit v1.
+#![no_std]
+
+mod frame;
+mod packet;
+#[cfg(feature = "v1")]
+mod v1;
+
+pub use frame::{decode_frame, encode_frame, FrameBuffer, MAX_FRAME_LEN, MAX_PAYLOAD_LEN};
+pub use packet::{Packet, Payload, Text, MAX_PACKET_LEN};
+#[cfg(feature = "v1")]
+pub use v1::{Datagram, Radio};
+
+/// The group used by the micro:bit runtime unless another one is set.
+pub const DEFAULT_GROUP: u8 = 0;
+/// The channel used by the micro:bit runtime, at 2407 MHz.
+pub const DEFAULT_CHANNEL: u8 = 7;
+/// The highest channel allowed by the micro:bit runtime, at 2483 MHz.
+pub const MAX_CHANNEL: u8 = 83;
+/// The transmit power level used by the micro:bit runtime, 0 dBm.
+pub const DEFAULT_POWER: u8 = 6;
+/// The highest transmit power level, +4 dBm.
+pub const MAX_POWER: u8 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The payload does not fit in a frame, or a name does not fit in a packet.
+    TooLong,
+    /// The data ended before the end of the packet.
+    TooShort,
+    /// The packet type is not one of the MakeCode packet types.
+    UnknownType(u8),
+    /// A string in the packet is not valid UTF-8.
+    InvalidText,
+    /// The channel is above `MAX_CHANNEL`.
+    InvalidChannel,
+}
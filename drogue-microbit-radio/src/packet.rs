@@ -0,0 +1,321 @@
+use core::convert::TryInto;
+
+use heapless::{consts, String, Vec};
+
+use crate::Error;
+
+/// Length of the packet type, time and serial number before the data.
+const PREFIX_LEN: usize = 9;
+/// Maximum length of the data after the prefix.
+const MAX_DATA_LEN: usize = 20;
+/// Maximum length of the names of values sent by MakeCode.
+const MAX_NAME_LEN: usize = 8;
+/// Maximum length of strings and buffers, after their length byte.
+const MAX_TEXT_LEN: usize = MAX_DATA_LEN - 1;
+
+/// Maximum length of an encoded packet.
+pub const MAX_PACKET_LEN: usize = PREFIX_LEN + MAX_DATA_LEN;
+
+const NUMBER: u8 = 0;
+const VALUE: u8 = 1;
+const STRING: u8 = 2;
+const BUFFER: u8 = 3;
+const DOUBLE: u8 = 4;
+const DOUBLE_VALUE: u8 = 5;
+
+/// A string in a packet.
+pub type Text = String<consts::U19>;
+
+/// The data of a MakeCode radio packet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payload {
+    Number(i32),
+    Value { name: Text, value: i32 },
+    String(Text),
+    Buffer(Vec<u8, consts::U19>),
+    Double(f64),
+    DoubleValue { name: Text, value: f64 },
+}
+
+impl Payload {
+    /// A number, sent as an integer when it has no fraction like MakeCode does.
+    pub fn number(value: f64) -> Self {
+        match integer(value) {
+            Some(value) => Payload::Number(value),
+            None => Payload::Double(value),
+        }
+    }
+
+    /// A name and value pair, where the name is truncated to 8 bytes.
+    pub fn value(name: &str, value: f64) -> Self {
+        let name = text(name, MAX_NAME_LEN);
+        match integer(value) {
+            Some(value) => Payload::Value { name, value },
+            None => Payload::DoubleValue { name, value },
+        }
+    }
+
+    /// A string, truncated to 19 bytes.
+    pub fn string(value: &str) -> Self {
+        Payload::String(text(value, MAX_TEXT_LEN))
+    }
+}
+
+/// A packet sent by the MakeCode radio blocks.
+///
+/// ```
+/// use drogue_microbit_radio::{Packet, Payload, MAX_PACKET_LEN};
+///
+/// let packet = Packet::new(1000, 0, Payload::value("speed", 12.5));
+/// let mut buffer = [0; MAX_PACKET_LEN];
+/// let len = packet.encode(&mut buffer).unwrap();
+/// assert_eq!(Packet::decode(&buffer[..len]), Ok(packet));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet {
+    /// The running time of the sender in milliseconds.
+    pub time: u32,
+    /// The serial number of the sender, or 0 if it is not transmitted.
+    pub serial: u32,
+    pub payload: Payload,
+}
+
+impl Packet {
+    pub fn new(time: u32, serial: u32, payload: Payload) -> Self {
+        Self {
+            time,
+            serial,
+            payload,
+        }
+    }
+
+    /// Encode the packet, returning its length. Fails with `Error::TooLong` if the name of a value
+    /// does not fit, which is after 15 bytes for integers and 11 bytes for doubles.
+    pub fn encode(&self, buffer: &mut [u8; MAX_PACKET_LEN]) -> Result<usize, Error> {
+        buffer[1..5].copy_from_slice(&self.time.to_le_bytes());
+        buffer[5..9].copy_from_slice(&self.serial.to_le_bytes());
+        let data = &mut buffer[PREFIX_LEN..];
+        let (packet_type, len) = match &self.payload {
+            Payload::Number(value) => {
+                data[..4].copy_from_slice(&value.to_le_bytes());
+                (NUMBER, 4)
+            }
+            Payload::Value { name, value } => {
+                data[..4].copy_from_slice(&value.to_le_bytes());
+                (VALUE, 4 + write_bytes(&mut data[4..], name.as_bytes())?)
+            }
+            Payload::String(value) => (STRING, write_bytes(data, value.as_bytes())?),
+            Payload::Buffer(value) => (BUFFER, write_bytes(data, value)?),
+            Payload::Double(value) => {
+                data[..8].copy_from_slice(&value.to_le_bytes());
+                (DOUBLE, 8)
+            }
+            Payload::DoubleValue { name, value } => {
+                data[..8].copy_from_slice(&value.to_le_bytes());
+                (
+                    DOUBLE_VALUE,
+                    8 + write_bytes(&mut data[8..], name.as_bytes())?,
+                )
+            }
+        };
+        buffer[0] = packet_type;
+        Ok(PREFIX_LEN + len)
+    }
+
+    pub fn decode(packet: &[u8]) -> Result<Self, Error> {
+        if packet.len() < PREFIX_LEN {
+            return Err(Error::TooShort);
+        }
+        let time = u32::from_le_bytes(packet[1..5].try_into().unwrap());
+        let serial = u32::from_le_bytes(packet[5..9].try_into().unwrap());
+        let data = &packet[PREFIX_LEN..];
+        let payload = match packet[0] {
+            NUMBER => Payload::Number(read_i32(data)?),
+            VALUE => Payload::Value {
+                value: read_i32(data)?,
+                name: read_text(&data[4..])?,
+            },
+            STRING => Payload::String(read_text(data)?),
+            BUFFER => {
+                let mut buffer = Vec::new();
+                buffer
+                    .extend_from_slice(read_bytes(data)?)
+                    .map_err(|_| Error::TooLong)?;
+                Payload::Buffer(buffer)
+            }
+            DOUBLE => Payload::Double(read_f64(data)?),
+            DOUBLE_VALUE => Payload::DoubleValue {
+                value: read_f64(data)?,
+                name: read_text(&data[8..])?,
+            },
+            packet_type => return Err(Error::UnknownType(packet_type)),
+        };
+        Ok(Self::new(time, serial, payload))
+    }
+}
+
+/// The value as an integer, if it has no fraction and fits.
+fn integer(value: f64) -> Option<i32> {
+    let integer = value as i32;
+    if f64::from(integer) == value {
+        Some(integer)
+    } else {
+        None
+    }
+}
+
+/// Copy at most `max` bytes of `value`, without splitting a character.
+fn text(value: &str, max: usize) -> Text {
+    let mut end = core::cmp::min(value.len(), max);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut text = Text::new();
+    text.push_str(&value[..end]).unwrap();
+    text
+}
+
+/// Write a length byte followed by the bytes, returning the length written.
+fn write_bytes(data: &mut [u8], bytes: &[u8]) -> Result<usize, Error> {
+    if 1 + bytes.len() > data.len() {
+        return Err(Error::TooLong);
+    }
+    data[0] = bytes.len() as u8;
+    data[1..1 + bytes.len()].copy_from_slice(bytes);
+    Ok(1 + bytes.len())
+}
+
+fn read_i32(data: &[u8]) -> Result<i32, Error> {
+    let bytes = data.get(..4).ok_or(Error::TooShort)?;
+    Ok(i32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_f64(data: &[u8]) -> Result<f64, Error> {
+    let bytes = data.get(..8).ok_or(Error::TooShort)?;
+    Ok(f64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Read bytes preceded by their length.
+fn read_bytes(data: &[u8]) -> Result<&[u8], Error> {
+    let len = *data.first().ok_or(Error::TooShort)? as usize;
+    data.get(1..1 + len).ok_or(Error::TooShort)
+}
+
+fn read_text(data: &[u8]) -> Result<Text, Error> {
+    let value = core::str::from_utf8(read_bytes(data)?).map_err(|_| Error::InvalidText)?;
+    let mut text = Text::new();
+    text.push_str(value).map_err(|_| Error::TooLong)?;
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(packet: &Packet) -> ([u8; MAX_PACKET_LEN], usize) {
+        let mut buffer = [0; MAX_PACKET_LEN];
+        let len = packet.encode(&mut buffer).unwrap();
+        assert_eq!(Packet::decode(&buffer[..len]).as_ref(), Ok(packet));
+        (buffer, len)
+    }
+
+    #[test]
+    fn number() {
+        let packet = Packet::new(0x01020304, 0xAABBCCDD, Payload::number(-2.0));
+        assert_eq!(packet.payload, Payload::Number(-2));
+        let (buffer, len) = encode(&packet);
+        assert_eq!(
+            buffer[..len],
+            [0, 4, 3, 2, 1, 0xDD, 0xCC, 0xBB, 0xAA, 0xFE, 0xFF, 0xFF, 0xFF]
+        );
+
+        let packet = Packet::new(0, 0, Payload::number(0.5));
+        assert_eq!(packet.payload, Payload::Double(0.5));
+        let (buffer, len) = encode(&packet);
+        assert_eq!(len, 17);
+        assert_eq!(buffer[0], DOUBLE);
+        assert_eq!(buffer[9..17], 0.5f64.to_le_bytes());
+
+        // Too large for an integer
+        assert_eq!(Payload::number(3e9), Payload::Double(3e9));
+    }
+
+    #[test]
+    fn values() {
+        let packet = Packet::new(10, 0, Payload::value("temperature", 21.0));
+        let (buffer, len) = encode(&packet);
+        assert_eq!(
+            buffer[..len],
+            [
+                1, 10, 0, 0, 0, 0, 0, 0, 0, 21, 0, 0, 0, 8, b't', b'e', b'm', b'p', b'e', b'r',
+                b'a', b't'
+            ]
+        );
+
+        let packet = Packet::new(10, 0, Payload::value("x", -0.25));
+        let (buffer, len) = encode(&packet);
+        assert_eq!(len, 9 + 8 + 2);
+        assert_eq!(buffer[0], DOUBLE_VALUE);
+        assert_eq!(buffer[17..19], [1, b'x']);
+
+        // Longer names than MakeCode sends fit up to the end of the packet
+        let name = Text::from("0123456789abcde");
+        let packet = Packet::new(0, 0, Payload::Value { name, value: 1 });
+        let (_, len) = encode(&packet);
+        assert_eq!(len, MAX_PACKET_LEN);
+
+        let mut buffer = [0; MAX_PACKET_LEN];
+        let name = Text::from("0123456789abcdefghi");
+        let packet = Packet::new(0, 0, Payload::Value { name, value: 1 });
+        assert_eq!(packet.encode(&mut buffer), Err(Error::TooLong));
+        let name = Text::from("0123456789ab");
+        let packet = Packet::new(0, 0, Payload::DoubleValue { name, value: 0.5 });
+        assert_eq!(packet.encode(&mut buffer), Err(Error::TooLong));
+    }
+
+    #[test]
+    fn strings() {
+        let packet = Packet::new(0, 0, Payload::string("hello"));
+        let (buffer, len) = encode(&packet);
+        assert_eq!(buffer[0], STRING);
+        assert_eq!(buffer[9..len], [5, b'h', b'e', b'l', b'l', b'o']);
+
+        // Truncated to fit, without splitting the last character
+        let packet = Packet::new(0, 0, Payload::string("0123456789abcdefghijklmnop"));
+        let (buffer, len) = encode(&packet);
+        assert_eq!(len, MAX_PACKET_LEN);
+        assert_eq!(buffer[9], 19);
+        assert_eq!(
+            Payload::string("0123456789abcdefghé"),
+            Payload::string("0123456789abcdefgh")
+        );
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&[1, 2, 3]).unwrap();
+        let packet = Packet::new(0, 0, Payload::Buffer(data));
+        let (buffer, len) = encode(&packet);
+        assert_eq!(buffer[..len], [3, 0, 0, 0, 0, 0, 0, 0, 0, 3, 1, 2, 3]);
+    }
+
+    #[test]
+    fn invalid_packets() {
+        assert_eq!(Packet::decode(&[0; 8]), Err(Error::TooShort));
+        assert_eq!(Packet::decode(&[0; 12]), Err(Error::TooShort));
+        assert_eq!(Packet::decode(&[6; 13]), Err(Error::UnknownType(6)));
+        assert_eq!(
+            Packet::decode(&[2, 0, 0, 0, 0, 0, 0, 0, 0, 3, b'a', b'b']),
+            Err(Error::TooShort)
+        );
+        assert_eq!(
+            Packet::decode(&[2, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0xC3, 0x28]),
+            Err(Error::InvalidText)
+        );
+        assert_eq!(
+            Packet::decode(&[
+                2, 0, 0, 0, 0, 0, 0, 0, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0, 0, 0
+            ]),
+            Err(Error::TooLong)
+        );
+    }
+}
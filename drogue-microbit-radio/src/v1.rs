@@ -0,0 +1,224 @@
+use heapless::{consts, Vec};
+use nrf51_hal::pac::RADIO;
+
+use crate::{
+    decode_frame, encode_frame, Error, FrameBuffer, Packet, DEFAULT_CHANNEL, DEFAULT_GROUP,
+    DEFAULT_POWER, MAX_CHANNEL, MAX_FRAME_LEN, MAX_PACKET_LEN, MAX_POWER,
+};
+
+/// Base address shared by all micro:bits, "ubit". The group is the address prefix.
+const BASE_ADDRESS: u32 = 0x7562_6974;
+
+/// TXPOWER for each power level, from -30 dBm to +4 dBm.
+const POWER_LEVELS: [u8; MAX_POWER as usize + 1] = [0xD8, 0xEC, 0xF0, 0xF4, 0xF8, 0xFC, 0x00, 0x04];
+
+const MODE_NRF_1MBIT: u32 = 0;
+/// An 8 bit length field and no S0 and S1 fields.
+const PCNF0_LFLEN_8: u32 = 8;
+const PCNF1_WHITEEN: u32 = 1 << 25;
+const PCNF1_BALEN_4: u32 = 4 << 16;
+const CRCCNF_LEN_2: u32 = 2;
+const CRC_INIT: u32 = 0xFFFF;
+const CRC_POLY: u32 = 0x1_1021;
+const DATA_WHITE_IV: u32 = 0x18;
+const SHORTS_READY_START: u32 = 1 << 0;
+const SHORTS_ADDRESS_RSSISTART: u32 = 1 << 4;
+const INT_END: u32 = 1 << 3;
+const STATE_DISABLED: u32 = 0;
+
+/// The payload of a frame received in the group of the radio.
+pub struct Datagram {
+    pub data: Vec<u8, consts::U29>,
+    /// Received signal strength in dBm.
+    pub rssi: i8,
+}
+
+impl Datagram {
+    /// Decode the datagram as a MakeCode packet.
+    pub fn packet(&self) -> Result<Packet, Error> {
+        Packet::decode(&self.data)
+    }
+}
+
+/// The packet radio of the micro:bit runtime, using the RADIO peripheral in the proprietary
+/// 1 Mbit mode. The HFCLK must run from the external crystal.
+///
+/// Frames are received in the background once `start_receive` is called, and `on_interrupt` must
+/// be called from the RADIO interrupt handler to read them. Sending is blocking and takes less than
+/// a millisecond, after which reception is resumed.
+pub struct Radio {
+    radio: RADIO,
+    buffer: &'static mut FrameBuffer,
+    group: u8,
+    receiving: bool,
+}
+
+impl Radio {
+    /// Configure the radio like the micro:bit runtime, in the default group and channel at the
+    /// default power level.
+    pub fn new(radio: RADIO, buffer: &'static mut FrameBuffer) -> Self {
+        radio.power.write(|w| unsafe { w.bits(1) });
+        radio.mode.write(|w| unsafe { w.bits(MODE_NRF_1MBIT) });
+        radio
+            .txpower
+            .write(|w| unsafe { w.bits(u32::from(POWER_LEVELS[DEFAULT_POWER as usize])) });
+        radio
+            .frequency
+            .write(|w| unsafe { w.bits(u32::from(DEFAULT_CHANNEL)) });
+        radio.pcnf0.write(|w| unsafe { w.bits(PCNF0_LFLEN_8) });
+        radio
+            .pcnf1
+            .write(|w| unsafe { w.bits(PCNF1_WHITEEN | PCNF1_BALEN_4 | MAX_FRAME_LEN as u32) });
+        radio.base0.write(|w| unsafe { w.bits(BASE_ADDRESS) });
+        radio
+            .prefix0
+            .write(|w| unsafe { w.bits(u32::from(DEFAULT_GROUP)) });
+        radio.txaddress.write(|w| unsafe { w.bits(0) });
+        radio.rxaddresses.write(|w| unsafe { w.bits(1) });
+        radio.crccnf.write(|w| unsafe { w.bits(CRCCNF_LEN_2) });
+        radio.crcinit.write(|w| unsafe { w.bits(CRC_INIT) });
+        radio.crcpoly.write(|w| unsafe { w.bits(CRC_POLY) });
+        radio
+            .datawhiteiv
+            .write(|w| unsafe { w.bits(DATA_WHITE_IV) });
+        radio
+            .packetptr
+            .write(|w| unsafe { w.bits(buffer.as_ptr() as u32) });
+        radio
+            .shorts
+            .write(|w| unsafe { w.bits(SHORTS_READY_START | SHORTS_ADDRESS_RSSISTART) });
+        Self {
+            radio,
+            buffer,
+            group: DEFAULT_GROUP,
+            receiving: false,
+        }
+    }
+
+    pub fn group(&self) -> u8 {
+        self.group
+    }
+
+    /// Only send to and receive from micro:bits in `group`.
+    pub fn set_group(&mut self, group: u8) {
+        self.group = group;
+        self.reconfigure(|radio| radio.prefix0.write(|w| unsafe { w.bits(u32::from(group)) }));
+    }
+
+    /// Use the channel at 2400 + `channel` MHz, up to `MAX_CHANNEL`.
+    pub fn set_channel(&mut self, channel: u8) -> Result<(), Error> {
+        if channel > MAX_CHANNEL {
+            return Err(Error::InvalidChannel);
+        }
+        self.reconfigure(|radio| {
+            radio
+                .frequency
+                .write(|w| unsafe { w.bits(u32::from(channel)) })
+        });
+        Ok(())
+    }
+
+    /// Set the transmit power level from 0 (-30 dBm) to `MAX_POWER` (+4 dBm). Higher levels are
+    /// clamped.
+    pub fn set_power(&mut self, level: u8) {
+        let txpower = POWER_LEVELS[core::cmp::min(level, MAX_POWER) as usize];
+        self.reconfigure(|radio| {
+            radio
+                .txpower
+                .write(|w| unsafe { w.bits(u32::from(txpower)) })
+        });
+    }
+
+    /// Receive frames in the background, signalled by the RADIO interrupt.
+    pub fn start_receive(&mut self) {
+        self.disable();
+        self.receiving = true;
+        self.radio.events_end.reset();
+        self.radio.intenset.write(|w| unsafe { w.bits(INT_END) });
+        self.radio.tasks_rxen.write(|w| unsafe { w.bits(1) });
+    }
+
+    pub fn stop_receive(&mut self) {
+        self.receiving = false;
+        self.radio.intenclr.write(|w| unsafe { w.bits(INT_END) });
+        self.disable();
+    }
+
+    /// Handle the RADIO interrupt, returning the frame received if it was sent to the group of the
+    /// radio.
+    pub fn on_interrupt(&mut self) -> Option<Datagram> {
+        if self.radio.events_end.read().bits() == 0 {
+            return None;
+        }
+        self.radio.events_end.reset();
+
+        let crc_ok = self.radio.crcstatus.read().bits() == 1;
+        let rssi = -(self.radio.rssisample.read().bits() as i8);
+        let datagram = decode_frame(&*self.buffer, self.group)
+            .filter(|_| crc_ok)
+            .and_then(|payload| {
+                let mut data = Vec::new();
+                data.extend_from_slice(payload).ok()?;
+                Some(Datagram { data, rssi })
+            });
+
+        // Wait for the next frame
+        if self.receiving {
+            self.radio.tasks_start.write(|w| unsafe { w.bits(1) });
+        }
+        datagram
+    }
+
+    /// Send a frame with `payload` to the group of the radio.
+    pub fn send(&mut self, payload: &[u8]) -> Result<(), Error> {
+        let receiving = self.receiving;
+        self.stop_receive();
+
+        encode_frame(self.buffer, self.group, payload)?;
+        self.radio.events_end.reset();
+        self.radio.tasks_txen.write(|w| unsafe { w.bits(1) });
+        while self.radio.events_end.read().bits() == 0 {}
+        self.radio.events_end.reset();
+        self.disable();
+
+        if receiving {
+            self.start_receive();
+        }
+        Ok(())
+    }
+
+    /// Send a MakeCode packet to the group of the radio.
+    pub fn send_packet(&mut self, packet: &Packet) -> Result<(), Error> {
+        let mut buffer = [0; MAX_PACKET_LEN];
+        let len = packet.encode(&mut buffer)?;
+        self.send(&buffer[..len])
+    }
+
+    pub fn free(mut self) -> RADIO {
+        self.stop_receive();
+        self.radio
+    }
+
+    /// Apply a configuration change with the radio disabled, and then resume reception.
+    fn reconfigure<F>(&mut self, f: F)
+    where
+        F: FnOnce(&RADIO),
+    {
+        let receiving = self.receiving;
+        self.stop_receive();
+        f(&self.radio);
+        if receiving {
+            self.start_receive();
+        }
+    }
+
+    fn disable(&mut self) {
+        if self.radio.state.read().bits() == STATE_DISABLED {
+            return;
+        }
+        self.radio.events_disabled.reset();
+        self.radio.tasks_disable.write(|w| unsafe { w.bits(1) });
+        while self.radio.events_disabled.read().bits() == 0 {}
+        self.radio.events_disabled.reset();
+    }
+}
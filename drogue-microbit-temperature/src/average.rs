@@ -0,0 +1,88 @@
+use heapless::{ArrayLength, Vec};
+
+/// Moving average of the last `N` values.
+pub struct Average<N>
+where
+    N: ArrayLength<i32>,
+{
+    values: Vec<i32, N>,
+    next: usize,
+    sum: i64,
+}
+
+impl<N> Average<N>
+where
+    N: ArrayLength<i32>,
+{
+    pub fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            next: 0,
+            sum: 0,
+        }
+    }
+
+    /// Add a value, replacing the oldest one when `N` values have been added.
+    pub fn add(&mut self, value: i32) {
+        if self.values.push(value).is_err() {
+            self.sum -= i64::from(self.values[self.next]);
+            self.values[self.next] = value;
+            self.next = (self.next + 1) % self.values.len();
+        }
+        self.sum += i64::from(value);
+    }
+
+    /// The average rounded to the nearest integer, or `None` if no values have been added.
+    pub fn value(&self) -> Option<i32> {
+        let count = self.values.len() as i64;
+        if count == 0 {
+            return None;
+        }
+        let half = if self.sum < 0 { -count / 2 } else { count / 2 };
+        Some(((self.sum + half) / count) as i32)
+    }
+
+    pub fn clear(&mut self) {
+        self.values = Vec::new();
+        self.next = 0;
+        self.sum = 0;
+    }
+}
+
+impl<N> Default for Average<N>
+where
+    N: ArrayLength<i32>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heapless::consts;
+
+    #[test]
+    fn moving_average() {
+        let mut average: Average<consts::U3> = Average::new();
+        assert_eq!(average.value(), None);
+
+        average.add(10);
+        assert_eq!(average.value(), Some(10));
+        average.add(13);
+        assert_eq!(average.value(), Some(12));
+        average.add(16);
+        assert_eq!(average.value(), Some(13));
+        // 10 is replaced
+        average.add(1);
+        assert_eq!(average.value(), Some(10));
+        average.add(-30);
+        assert_eq!(average.value(), Some(-4));
+        average.add(-30);
+        assert_eq!(average.value(), Some(-20));
+
+        average.clear();
+        assert_eq!(average.value(), None);
+    }
+}
@@ -0,0 +1,204 @@
+//! Calibrated temperature measurements with the on-die temperature sensor of the nRF51.
+//!
+//! The sensor measures the temperature of the chip in steps of 0.25 degrees Celsius, which is a few
+//! degrees above the ambient temperature while the radio and CPU are running. `Thermometer`
+//! corrects for this with a per-device offset and averages the measurements. The logic is
+//! independent of the hardware, and with the `v1` feature it is implemented for `hal::Temp`.
+#![no_std]
+
+use heapless::{consts, ArrayLength};
+
+mod average;
+#[cfg(feature = "v1")]
+mod v1;
+
+pub use average::Average;
+pub use fixed::types::I30F2;
+#[cfg(feature = "v1")]
+pub use v1::{calibration_offset, CALIBRATION_ADDRESS};
+
+/// A temperature in degrees Celsius, with the 0.25 degree resolution of the sensor.
+pub type Celsius = I30F2;
+
+/// Offset used when the device has not been calibrated, the typical difference between the die
+/// and the ambient temperature of a micro:bit advertising over BLE.
+pub const DEFAULT_OFFSET: Celsius = Celsius::from_bits(-4 << 2);
+
+/// A temperature sensor where a measurement is started and read back when it completes.
+pub trait Sensor {
+    fn start_measurement(&mut self);
+
+    /// The temperature if the measurement has completed.
+    fn read(&mut self) -> Option<Celsius>;
+
+    fn stop_measurement(&mut self);
+}
+
+enum State {
+    Idle,
+    Measuring,
+}
+
+/// Non-blocking thermometer, which alternates between starting a measurement and reading it.
+///
+/// `poll` should be called periodically, for example from a timer interrupt. The first call
+/// starts a measurement, and a later call reads it once it has completed, which takes 36 µs on the
+/// nRF51. The temperature reported is the average of the last `N` measurements plus the
+/// calibration offset.
+///
+/// ```
+/// use drogue_microbit_temperature::{Celsius, Sensor, Thermometer};
+///
+/// struct Fixed;
+///
+/// impl Sensor for Fixed {
+///     fn start_measurement(&mut self) {}
+///     fn read(&mut self) -> Option<Celsius> {
+///         Some(Celsius::from_num(25.5))
+///     }
+///     fn stop_measurement(&mut self) {}
+/// }
+///
+/// let mut thermometer: Thermometer<_> = Thermometer::new(Fixed, Celsius::from_num(-4));
+/// assert_eq!(thermometer.poll(), None);
+/// assert_eq!(thermometer.poll(), Some(Celsius::from_num(21.5)));
+/// ```
+pub struct Thermometer<S, N = consts::U4>
+where
+    N: ArrayLength<i32>,
+{
+    sensor: S,
+    offset: Celsius,
+    state: State,
+    average: Average<N>,
+}
+
+impl<S, N> Thermometer<S, N>
+where
+    S: Sensor,
+    N: ArrayLength<i32>,
+{
+    pub fn new(sensor: S, offset: Celsius) -> Self {
+        Self {
+            sensor,
+            offset,
+            state: State::Idle,
+            average: Average::new(),
+        }
+    }
+
+    /// The calibration offset added to the measurements.
+    pub fn offset(&self) -> Celsius {
+        self.offset
+    }
+
+    pub fn set_offset(&mut self, offset: Celsius) {
+        self.offset = offset;
+    }
+
+    /// Returns true if a measurement has been started and not yet read.
+    pub fn is_measuring(&self) -> bool {
+        matches!(self.state, State::Measuring)
+    }
+
+    /// Start a measurement, or read the one in progress. Returns the new calibrated average when a
+    /// measurement has completed.
+    pub fn poll(&mut self) -> Option<Celsius> {
+        match self.state {
+            State::Idle => {
+                self.sensor.start_measurement();
+                self.state = State::Measuring;
+                None
+            }
+            State::Measuring => {
+                let value = self.sensor.read()?;
+                self.sensor.stop_measurement();
+                self.state = State::Idle;
+                self.average.add(value.to_bits());
+                self.temperature()
+            }
+        }
+    }
+
+    /// The calibrated average of the last measurements, or `None` before the first measurement.
+    pub fn temperature(&self) -> Option<Celsius> {
+        self.average
+            .value()
+            .map(|bits| Celsius::from_bits(bits).saturating_add(self.offset))
+    }
+
+    pub fn free(self) -> S {
+        self.sensor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct MockSensor {
+        measuring: bool,
+        polls: u8,
+        value: i32,
+    }
+
+    impl Sensor for MockSensor {
+        fn start_measurement(&mut self) {
+            assert!(!self.measuring);
+            self.measuring = true;
+            self.polls = 0;
+        }
+
+        fn read(&mut self) -> Option<Celsius> {
+            assert!(self.measuring);
+            self.polls += 1;
+            // Completes on the second read
+            if self.polls < 2 {
+                None
+            } else {
+                Some(Celsius::from_bits(self.value))
+            }
+        }
+
+        fn stop_measurement(&mut self) {
+            self.measuring = false;
+        }
+    }
+
+    #[test]
+    fn measurement_cycle() {
+        let mut thermometer: Thermometer<_, consts::U2> =
+            Thermometer::new(MockSensor::default(), DEFAULT_OFFSET);
+        assert_eq!(thermometer.temperature(), None);
+
+        thermometer.sensor.value = 2;
+        assert_eq!(thermometer.poll(), None);
+        assert!(thermometer.is_measuring());
+        assert_eq!(thermometer.poll(), None);
+        // 0.5 - 4.0
+        assert_eq!(thermometer.poll(), Some(Celsius::from_num(-3.5)));
+        assert!(!thermometer.is_measuring());
+        assert!(!thermometer.sensor.measuring);
+
+        thermometer.sensor.value = 91;
+        for _ in 0..2 {
+            assert_eq!(thermometer.poll(), None);
+        }
+        // (0.5 + 22.75) / 2 = 11.625 is rounded to the resolution of the sensor
+        assert_eq!(thermometer.poll(), Some(Celsius::from_num(7.75)));
+
+        thermometer.set_offset(Celsius::from_num(0));
+        assert_eq!(thermometer.temperature(), Some(Celsius::from_num(11.75)));
+        assert_eq!(thermometer.offset(), Celsius::from_num(0));
+    }
+
+    #[test]
+    fn below_freezing() {
+        let mut thermometer: Thermometer<_> =
+            Thermometer::new(MockSensor::default(), DEFAULT_OFFSET);
+        thermometer.sensor.value = 9;
+        let temperature = (0..3).filter_map(|_| thermometer.poll()).next();
+        assert_eq!(temperature, Some(Celsius::from_num(-1.75)));
+    }
+}
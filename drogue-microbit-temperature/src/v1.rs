@@ -0,0 +1,36 @@
+use nrf51_hal::pac::UICR;
+use nrf51_hal::Temp;
+
+use crate::{Celsius, Sensor};
+
+/// Address of the UICR customer register holding the calibration offset, `CUSTOMER[0]`.
+///
+/// The offset is stored as a signed 32-bit number of 0.25 degrees Celsius, and is programmed along
+/// with the firmware, e.g. with `nrfjprog --memwr 0x10001080 --val 0xFFFFFFF4` for -3 degrees.
+pub const CALIBRATION_ADDRESS: u32 = 0x1000_1080;
+
+/// Value of an erased flash word.
+const ERASED: u32 = 0xFFFF_FFFF;
+
+/// The calibration offset of this device, or `None` if it has not been programmed.
+pub fn calibration_offset(uicr: &UICR) -> Option<Celsius> {
+    match uicr.customer[0].read().bits() {
+        ERASED => None,
+        bits => Some(Celsius::from_bits(bits as i32)),
+    }
+}
+
+impl Sensor for Temp {
+    fn start_measurement(&mut self) {
+        Temp::start_measurement(self);
+    }
+
+    fn read(&mut self) -> Option<Celsius> {
+        // The only error is that the measurement is still in progress
+        Temp::read(self).ok()
+    }
+
+    fn stop_measurement(&mut self) {
+        Temp::stop_measurement(self);
+    }
+}
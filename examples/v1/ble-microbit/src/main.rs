@@ -0,0 +1,424 @@
+//! Example of the micro:bit Bluetooth profile, letting the micro:bit apps control the LED matrix
+//! and read the buttons, temperature and accelerometer.
+#![no_main]
+#![no_std]
+
+#[allow(unused_imports)]
+use panic_halt;
+
+use drogue_microbit_accelerometer::{GestureDetector, Mma8653};
+use drogue_microbit_buttons::{Buttons, Event};
+use drogue_microbit_gatt::{Composite, GattProvider, GattTable, Properties};
+use drogue_microbit_matrix::LedMatrix;
+use drogue_microbit_profile::{
+    Acceleration, AccelerometerService, Button, ButtonService, ButtonState, LedCommand, LedService,
+    TemperatureService,
+};
+use drogue_microbit_temperature::{calibration_offset, Thermometer, DEFAULT_OFFSET};
+use heapless::consts;
+
+use nrf51_hal as hal;
+
+use core::sync::atomic::{compiler_fence, Ordering};
+use hal::gpio::{Level, Output, Pin, PushPull};
+use hal::rtc::{Rtc, RtcInterrupt};
+use log::LevelFilter;
+use rtt_logger::RTTLogger;
+use rtt_target::rtt_init_print;
+
+use rubble::l2cap::{BleChannelMap, L2CAPState};
+use rubble::link::queue::{PacketQueue, SimpleQueue};
+use rubble::link::{ad_structure::AdStructure, LinkLayer, Responder, MIN_PDU_BUF};
+use rubble::time::{Duration, Timer};
+use rubble::uuid::Uuid16;
+use rubble::{config::Config, security::NoSecurity};
+use rubble_nrf5x::radio::{BleRadio, PacketBuffer};
+use rubble_nrf5x::{timer::BleTimer, utils::get_device_address};
+
+static LOGGER: RTTLogger = RTTLogger::new(LevelFilter::Debug);
+
+/// The micro:bit apps look for devices with this name
+const DEVICE_NAME: &str = "BBC micro:bit";
+
+// RTC0 ticks at 32768 / (327 + 1) = 100 Hz
+const TICK_MS: u32 = 10;
+
+// The gesture detector is tuned for 50 Hz
+const ACCELEROMETER_MS: u32 = 20;
+
+/// Generic Access service followed by the LED, Button, Temperature and Accelerometer services
+type Services = Composite<
+    Composite<
+        Composite<Composite<GattTable<consts::U3>, LedService>, ButtonService>,
+        TemperatureService,
+    >,
+    AccelerometerService,
+>;
+
+type Led = LedMatrix<Pin<Output<PushPull>>>;
+
+type Accelerometer = Mma8653<hal::Twi<hal::pac::TWI1>>;
+
+use rtic::app;
+
+pub enum AppConfig {}
+
+impl Config for AppConfig {
+    type Timer = BleTimer<hal::pac::TIMER0>;
+    type Transmitter = BleRadio;
+    type ChannelMapper = BleChannelMap<Services, NoSecurity>;
+    type PacketQueue = &'static mut SimpleQueue;
+}
+
+#[app(device = crate::hal::pac, peripherals = true)]
+const APP: () = {
+    struct Resources {
+        rtc: Rtc<hal::pac::RTC0>,
+        display_rtc: Rtc<hal::pac::RTC1>,
+        led: Led,
+        buttons: Buttons,
+        #[init(0)]
+        now_ms: u32,
+        thermometer: Thermometer<hal::Temp>,
+        #[init(0)]
+        temperature_ms: u32,
+        accelerometer: Accelerometer,
+        gestures: GestureDetector,
+        #[init(0)]
+        accelerometer_ms: u32,
+
+        #[init([0; MIN_PDU_BUF])]
+        ble_tx_buf: PacketBuffer,
+        #[init([0; MIN_PDU_BUF])]
+        ble_rx_buf: PacketBuffer,
+        #[init(SimpleQueue::new())]
+        tx_queue: SimpleQueue,
+        #[init(SimpleQueue::new())]
+        rx_queue: SimpleQueue,
+        ble_ll: LinkLayer<AppConfig>,
+        ble_r: Responder<AppConfig>,
+        radio: BleRadio,
+    }
+
+    #[init(resources = [ble_tx_buf, ble_rx_buf, tx_queue, rx_queue])]
+    fn init(ctx: init::Context) -> init::LateResources {
+        rtt_init_print!();
+        log::set_max_level(log::LevelFilter::Debug);
+        unsafe {
+            log::set_logger_racy(&LOGGER).unwrap();
+        }
+
+        // The LED matrix takes most of port 0, so the pins are split here to keep the buttons
+        let port0 = hal::gpio::p0::Parts::new(ctx.device.GPIO);
+        let led = LedMatrix::from_pins(
+            [
+                port0.p0_13.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_14.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_15.into_push_pull_output(Level::Low).degrade(),
+            ],
+            [
+                port0.p0_04.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_05.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_06.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_07.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_08.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_09.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_10.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_11.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_12.into_push_pull_output(Level::Low).degrade(),
+            ],
+        );
+        let buttons = Buttons::new(
+            ctx.device.GPIOTE,
+            port0.p0_17.into_floating_input(),
+            port0.p0_26.into_floating_input(),
+        );
+
+        // The accelerometer is on the internal I2C bus
+        let twi = hal::Twi::new(
+            ctx.device.TWI1,
+            hal::twi::Pins {
+                scl: port0.p0_00.into_floating_input().degrade(),
+                sda: port0.p0_30.into_floating_input().degrade(),
+            },
+            hal::twi::Frequency::K100,
+        );
+        let accelerometer = Mma8653::new(twi).unwrap();
+
+        let clocks = hal::clocks::Clocks::new(ctx.device.CLOCK).enable_ext_hfosc();
+        let _clocks = clocks.start_lfclk();
+
+        let offset = calibration_offset(&ctx.device.UICR).unwrap_or(DEFAULT_OFFSET);
+        let thermometer = Thermometer::new(hal::Temp::new(ctx.device.TEMP), offset);
+
+        let ble_timer = BleTimer::init(ctx.device.TIMER0);
+
+        let mut rtc = Rtc::new(ctx.device.RTC0, 327).unwrap();
+        rtc.enable_event(RtcInterrupt::Tick);
+        rtc.enable_counter();
+        rtc.enable_interrupt(RtcInterrupt::Tick, None);
+
+        // Refresh the display at 4096 Hz, which gives ~150 frames per second
+        let mut display_rtc = Rtc::new(ctx.device.RTC1, 7).unwrap();
+        display_rtc.enable_event(RtcInterrupt::Tick);
+        display_rtc.enable_counter();
+        display_rtc.enable_interrupt(RtcInterrupt::Tick, None);
+
+        let device_address = get_device_address();
+
+        log::info!("Starting to advertise with address {:?}", device_address);
+
+        let mut radio = BleRadio::new(
+            ctx.device.RADIO,
+            &ctx.device.FICR,
+            ctx.resources.ble_tx_buf,
+            ctx.resources.ble_rx_buf,
+        );
+
+        // Create TX/RX queues
+        let (tx, tx_cons) = ctx.resources.tx_queue.split();
+        let (rx_prod, rx) = ctx.resources.rx_queue.split();
+
+        // Create the actual BLE stack objects
+        let mut ble_ll = LinkLayer::<AppConfig>::new(device_address, ble_timer);
+
+        let mut gap = GattTable::builder();
+        gap.service(Uuid16(0x1800)).unwrap();
+        gap.characteristic(Uuid16(0x2A00), Properties::READ, DEVICE_NAME.as_bytes())
+            .unwrap();
+
+        let services = gap
+            .build()
+            .chain(LedService::new())
+            .chain(ButtonService::new())
+            .chain(TemperatureService::new())
+            .chain(AccelerometerService::new());
+
+        let ble_r = Responder::new(
+            tx,
+            rx,
+            L2CAPState::new(BleChannelMap::with_attributes(services)),
+        );
+
+        let next_update = ble_ll
+            .start_advertise(
+                Duration::from_millis(100),
+                &[AdStructure::CompleteLocalName(DEVICE_NAME)],
+                &mut radio,
+                tx_cons,
+                rx_prod,
+            )
+            .unwrap();
+
+        ble_ll.timer().configure_interrupt(next_update);
+
+        init::LateResources {
+            radio: radio,
+            ble_ll: ble_ll,
+            ble_r: ble_r,
+            rtc: rtc,
+            display_rtc: display_rtc,
+            led: led,
+            buttons: buttons,
+            thermometer: thermometer,
+            accelerometer: accelerometer,
+            gestures: GestureDetector::new(),
+        }
+    }
+
+    #[task(binds = RADIO, resources = [radio, ble_ll], spawn = [ble_worker], priority = 3)]
+    fn radio(ctx: radio::Context) {
+        let ble_ll: &mut LinkLayer<AppConfig> = ctx.resources.ble_ll;
+        if let Some(cmd) = ctx
+            .resources
+            .radio
+            .recv_interrupt(ble_ll.timer().now(), ble_ll)
+        {
+            ctx.resources.radio.configure_receiver(cmd.radio);
+            ble_ll.timer().configure_interrupt(cmd.next_update);
+
+            if cmd.queued_work {
+                // If there's any lower-priority work to be done, ensure that happens.
+                // If we fail to spawn the task, it's already scheduled.
+                ctx.spawn.ble_worker().ok();
+            }
+        }
+    }
+
+    #[task(binds = TIMER0, resources = [radio, ble_ll], spawn = [ble_worker], priority = 3)]
+    fn timer0(ctx: timer0::Context) {
+        let timer = ctx.resources.ble_ll.timer();
+        if !timer.is_interrupt_pending() {
+            return;
+        }
+        timer.clear_interrupt();
+
+        let cmd = ctx.resources.ble_ll.update_timer(ctx.resources.radio);
+        ctx.resources.radio.configure_receiver(cmd.radio);
+
+        ctx.resources
+            .ble_ll
+            .timer()
+            .configure_interrupt(cmd.next_update);
+
+        if cmd.queued_work {
+            // If there's any lower-priority work to be done, ensure that happens.
+            // If we fail to spawn the task, it's already scheduled.
+            ctx.spawn.ble_worker().ok();
+        }
+    }
+
+    #[task(binds = GPIOTE, resources = [buttons, now_ms], priority = 1)]
+    fn gpiote(ctx: gpiote::Context) {
+        let gpiote::Resources { buttons, now_ms } = ctx.resources;
+        buttons.on_interrupt(*now_ms);
+    }
+
+    #[task(binds = RTC0, resources = [rtc, led, buttons, now_ms, thermometer, temperature_ms, accelerometer, gestures, accelerometer_ms, ble_r], priority = 1)]
+    fn rtc0(ctx: rtc0::Context) {
+        let rtc0::Resources {
+            rtc,
+            mut led,
+            buttons,
+            now_ms,
+            thermometer,
+            temperature_ms,
+            accelerometer,
+            gestures,
+            accelerometer_ms,
+            mut ble_r,
+        } = ctx.resources;
+        rtc.reset_event(RtcInterrupt::Tick);
+        rtc.clear_counter();
+
+        *now_ms = now_ms.wrapping_add(TICK_MS);
+        buttons.update(*now_ms);
+        let mut states = [None; 2];
+        while let Some(event) = buttons.next_event() {
+            match event {
+                Event::Pressed(button) => states[button as usize] = Some(ButtonState::Pressed),
+                Event::LongPress(button) => {
+                    states[button as usize] = Some(ButtonState::LongPressed)
+                }
+                Event::Released(button) => states[button as usize] = Some(ButtonState::Released),
+                _ => {}
+            }
+        }
+
+        // The measurement is started one tick ahead of the sampling period, which leaves plenty of
+        // time for it to complete
+        *temperature_ms += TICK_MS;
+        let celsius = if thermometer.is_measuring() {
+            thermometer.poll()
+        } else {
+            None
+        };
+
+        *accelerometer_ms += TICK_MS;
+        let accel = if *accelerometer_ms % ACCELEROMETER_MS == 0 {
+            accelerometer.accel().ok()
+        } else {
+            None
+        };
+        if let Some(gesture) = accel.and_then(|accel| gestures.update(accel)) {
+            log::info!("Gesture: {:?}", gesture);
+        }
+
+        let command = ble_r.lock(|ble_r| {
+            let mut l2cap = ble_r.l2cap();
+            let (command, notifications) = {
+                let services = l2cap.channel_mapper().attribute_provider();
+
+                let accelerometer: &mut AccelerometerService = services.second();
+                // Samples are published at the period requested by the client, or the sampling
+                // rate if that is slower
+                if let Some(accel) = accel {
+                    if *accelerometer_ms >= u32::from(accelerometer.period()) {
+                        *accelerometer_ms = 0;
+                        accelerometer
+                            .set_acceleration(Acceleration::new(accel.x, accel.y, accel.z));
+                    }
+                }
+                let accel = accelerometer.take_notification();
+
+                let temperature: &mut TemperatureService = services.first().second();
+                if let Some(celsius) = celsius {
+                    temperature.set_temperature_celsius(celsius.to_num::<f32>());
+                }
+                if *temperature_ms + TICK_MS >= u32::from(temperature.period()) {
+                    *temperature_ms = 0;
+                    thermometer.poll();
+                }
+                let temperature = temperature.take_notification();
+
+                let button_service: &mut ButtonService = services.first().first().second();
+                for (button, state) in [Button::A, Button::B].iter().zip(states.iter()) {
+                    if let Some(state) = state {
+                        button_service.set_state(*button, *state);
+                    }
+                }
+                let buttons = [
+                    button_service.take_notification(),
+                    button_service.take_notification(),
+                ];
+
+                let led_service: &mut LedService = services.first().first().first().second();
+                let command = led_service.take_command();
+                if let Some(LedCommand::Show(image)) = &command {
+                    led_service.set_image(image);
+                }
+                (command, [accel, temperature, buttons[0], buttons[1]])
+            };
+
+            // Push the new values to subscribed clients, unless the TX queue is full
+            for notification in notifications.iter().flatten() {
+                if let Some(mut att) = l2cap.att() {
+                    att.notify_raw(notification.handle(), notification.value());
+                }
+            }
+            command
+        });
+
+        led.lock(|led| {
+            match command {
+                Some(LedCommand::Show(image)) => led.show(&image),
+                Some(LedCommand::Scroll { text, delay_ms }) => {
+                    led.scroll_text(&text, u32::from(delay_ms) / TICK_MS)
+                }
+                None => {}
+            }
+            led.scroll();
+        });
+    }
+
+    #[task(binds = RTC1, resources = [display_rtc, led], priority = 2)]
+    fn rtc1(ctx: rtc1::Context) {
+        let display_rtc: &mut Rtc<hal::pac::RTC1> = ctx.resources.display_rtc;
+        let led: &mut Led = ctx.resources.led;
+
+        display_rtc.reset_event(RtcInterrupt::Tick);
+        led.refresh();
+    }
+
+    #[idle]
+    fn idle(_ctx: idle::Context) -> ! {
+        log::info!("Drogue IoT micro:bit started!");
+        loop {
+            compiler_fence(Ordering::SeqCst);
+        }
+    }
+
+    #[task(resources = [ble_r], priority = 2)]
+    fn ble_worker(ctx: ble_worker::Context) {
+        let ble_worker::Resources { ble_r } = ctx.resources;
+
+        // Fully drain the packet queue
+        while ble_r.has_work() {
+            ble_r.process_one().unwrap();
+        }
+    }
+
+    extern "C" {
+        fn WDT();
+    }
+};
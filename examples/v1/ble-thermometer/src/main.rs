@@ -1,14 +1,22 @@
-//! Example of a BLE thermometer exposed using ESS (Environmental Sensing Service)
+//! Example of a BLE thermometer exposed using ESS (Environmental Sensing Service), with a console
+//! on the Nordic UART Service
 #![no_main]
 #![no_std]
 
 #[allow(unused_imports)]
 use panic_halt;
 
-use drogue_microbit_ess::{EnvironmentSensingService, ESS_UUID};
+use drogue_microbit_battery::{BatteryService, SupplyVoltage};
+use drogue_microbit_dis::{DeviceInformation, DeviceInformationService, SerialNumber};
+use drogue_microbit_ess::{EnvironmentSensingService, Sensor, Temperature, ESS_UUID};
+use drogue_microbit_gatt::{Composite, GattProvider, GattTable, Properties};
+use drogue_microbit_nus::UartService;
+use drogue_microbit_temperature::{calibration_offset, Thermometer, DEFAULT_OFFSET};
+use heapless::{consts, String};
 
 use nrf51_hal as hal;
 
+use core::fmt::Write;
 use core::sync::atomic::{compiler_fence, Ordering};
 use hal::rtc::{Rtc, RtcCompareReg, RtcInterrupt};
 use log::LevelFilter;
@@ -22,12 +30,34 @@ use rubble::link::{
     LinkLayer, Responder, MIN_PDU_BUF,
 };
 use rubble::time::{Duration, Timer};
+use rubble::uuid::Uuid16;
 use rubble::{config::Config, security::NoSecurity};
 use rubble_nrf5x::radio::{BleRadio, PacketBuffer};
 use rubble_nrf5x::{timer::BleTimer, utils::get_device_address};
 
 static LOGGER: RTTLogger = RTTLogger::new(LevelFilter::Debug);
 
+const DEVICE_NAME: &str = "Drogue IoT micro:bit";
+
+// RTC0 runs at 32768 / (4095 + 1) = 8 Hz, so the interrupt fires every 1.25 seconds
+const RTC_COMPARE: u32 = 10;
+const RTC_PERIOD_MS: u32 = RTC_COMPARE * 1000 / 8;
+
+/// Generic Access, Device Information, Environmental Sensing, Battery and Nordic UART services
+type Services = Composite<
+    Composite<
+        Composite<
+            Composite<GattTable<consts::U3>, DeviceInformationService>,
+            EnvironmentSensingService,
+        >,
+        BatteryService,
+    >,
+    UartService,
+>;
+
+/// A line of console input
+type Line = String<consts::U32>;
+
 use rtic::app;
 
 pub enum AppConfig {}
@@ -35,18 +65,88 @@ pub enum AppConfig {}
 impl Config for AppConfig {
     type Timer = BleTimer<hal::pac::TIMER0>;
     type Transmitter = BleRadio;
-    type ChannelMapper = BleChannelMap<EnvironmentSensingService, NoSecurity>;
+    type ChannelMapper = BleChannelMap<Services, NoSecurity>;
     type PacketQueue = &'static mut SimpleQueue;
 }
 
+/// Handle console input received on the Nordic UART Service, one command per line.
+fn console(services: &mut Services, line: &mut Line, uptime_ms: u32) {
+    while let Some(byte) = services.second().read_byte() {
+        match byte {
+            b'\r' | b'\n' => {
+                if !line.is_empty() {
+                    run_command(services, line.trim(), uptime_ms);
+                    line.clear();
+                }
+            }
+            byte => {
+                if line.push(byte as char).is_err() {
+                    writeln!(services.second(), "Line too long").ok();
+                    line.clear();
+                }
+            }
+        }
+    }
+}
+
+fn run_command(services: &mut Services, command: &str, uptime_ms: u32) {
+    let ess: &mut EnvironmentSensingService = services.first().first().second();
+    let temperature = ess
+        .find(Sensor::Temperature)
+        .map(|id| ess.value(id))
+        .filter(|value| *value != i32::from(Temperature::UNKNOWN.centi_celsius()));
+    let level = services.first().second().level();
+
+    let uart = services.second();
+    // Output that does not fit in the buffer is dropped
+    match command {
+        "temperature" => match temperature {
+            Some(centi) => {
+                let sign = if centi < 0 { "-" } else { "" };
+                let centi = centi.abs();
+                writeln!(uart, "{}{}.{:02} C", sign, centi / 100, centi % 100)
+            }
+            None => writeln!(uart, "No measurement yet"),
+        },
+        "battery" => writeln!(uart, "{}%", level),
+        "uptime" => writeln!(uart, "{} s", uptime_ms / 1000),
+        "help" => writeln!(uart, "Commands: temperature battery uptime"),
+        _ => writeln!(uart, "Unknown command, try help"),
+    }
+    .ok();
+}
+
+/// Send console output while there is room in the TX queue.
+fn flush_console(ble_r: &mut Responder<AppConfig>) {
+    let mut l2cap = ble_r.l2cap();
+    while l2cap.att().is_some() {
+        let notification = match l2cap
+            .channel_mapper()
+            .attribute_provider()
+            .second()
+            .take_notification()
+        {
+            Some(notification) => notification,
+            None => break,
+        };
+        if let Some(mut att) = l2cap.att() {
+            att.notify_raw(notification.handle(), notification.value());
+        }
+    }
+}
+
 #[app(device = crate::hal::pac, peripherals = true)]
 const APP: () = {
     struct Resources {
         // Temperature sensing
-        thermometer: hal::Temp,
+        thermometer: Thermometer<hal::Temp>,
+        supply: SupplyVoltage,
         rtc: Rtc<hal::pac::RTC0>,
         #[init(0)]
         timer_count: i8,
+        #[init(0)]
+        uptime_ms: u32,
+        console_line: Line,
 
         #[init([0; MIN_PDU_BUF])]
         ble_tx_buf: PacketBuffer,
@@ -74,14 +174,16 @@ const APP: () = {
         let clocks = hal::clocks::Clocks::new(ctx.device.CLOCK).enable_ext_hfosc();
         let _clocks = clocks.start_lfclk();
 
-        let thermometer = hal::Temp::new(ctx.device.TEMP);
+        let offset = calibration_offset(&ctx.device.UICR).unwrap_or(DEFAULT_OFFSET);
+        let thermometer = Thermometer::new(hal::Temp::new(ctx.device.TEMP), offset);
+        let mut supply = SupplyVoltage::new(ctx.device.ADC);
 
         let ble_timer = BleTimer::init(ctx.device.TIMER0);
 
         let mut rtc = Rtc::new(ctx.device.RTC0, 4095).unwrap();
         rtc.enable_event(RtcInterrupt::Compare0);
         rtc.enable_counter();
-        let _ = rtc.set_compare(RtcCompareReg::Compare0, 10);
+        let _ = rtc.set_compare(RtcCompareReg::Compare0, RTC_COMPARE);
         rtc.enable_interrupt(RtcInterrupt::Compare0, None);
 
         let device_address = get_device_address();
@@ -102,15 +204,45 @@ const APP: () = {
         // Create the actual BLE stack objects
         let mut ble_ll = LinkLayer::<AppConfig>::new(device_address, ble_timer);
 
-        let ess: EnvironmentSensingService = EnvironmentSensingService::new();
+        let mut gap = GattTable::builder();
+        gap.service(Uuid16(0x1800)).unwrap();
+        gap.characteristic(Uuid16(0x2A00), Properties::READ, DEVICE_NAME.as_bytes())
+            .unwrap();
+
+        let serial = SerialNumber::from_device_id([
+            ctx.device.FICR.deviceid[0].read().bits(),
+            ctx.device.FICR.deviceid[1].read().bits(),
+        ]);
+        let dis = DeviceInformationService::new(&DeviceInformation {
+            manufacturer_name: "Drogue IoT",
+            model_number: "BBC micro:bit",
+            serial_number: serial.as_str(),
+            firmware_revision: env!("CARGO_PKG_VERSION"),
+            hardware_revision: "1",
+        })
+        .unwrap();
 
-        let ble_r = Responder::new(tx, rx, L2CAPState::new(BleChannelMap::with_attributes(ess)));
+        let ess: EnvironmentSensingService = EnvironmentSensingService::new();
+        let mut battery = BatteryService::default();
+        battery.set_voltage(supply.measure_millivolts());
+        let services = gap
+            .build()
+            .chain(dis)
+            .chain(ess)
+            .chain(battery)
+            .chain(UartService::new());
+
+        let ble_r = Responder::new(
+            tx,
+            rx,
+            L2CAPState::new(BleChannelMap::with_attributes(services)),
+        );
 
         let next_update = ble_ll
             .start_advertise(
                 Duration::from_millis(100),
                 &[
-                    AdStructure::CompleteLocalName("Drogue IoT micro:bit"),
+                    AdStructure::CompleteLocalName(DEVICE_NAME),
                     AdStructure::ServiceUuids16(ServiceUuids::from_uuids(true, &[ESS_UUID])),
                 ],
                 &mut radio,
@@ -126,6 +258,8 @@ const APP: () = {
             ble_ll: ble_ll,
             ble_r: ble_r,
             thermometer: thermometer,
+            supply: supply,
+            console_line: String::new(),
             rtc: rtc,
         }
     }
@@ -172,33 +306,51 @@ const APP: () = {
         }
     }
 
-    #[task(binds = RTC0, resources = [rtc, thermometer, timer_count, ble_r], priority = 1)]
+    #[task(binds = RTC0, resources = [rtc, thermometer, supply, timer_count, uptime_ms, ble_r], priority = 1)]
     fn rtc0(ctx: rtc0::Context) {
         let rtc0::Resources {
             rtc,
             thermometer,
+            supply,
             timer_count,
+            mut uptime_ms,
             mut ble_r,
         } = ctx.resources;
         rtc.reset_event(RtcInterrupt::Compare0);
         rtc.clear_counter();
-        if *timer_count % 2 == 0 {
-            thermometer.start_measurement();
-        } else {
-            let value = thermometer.read();
-            value.map_or_else(
-                |_| {},
-                |value| {
-                    let f = value.to_num::<u32>() - 4;
-                    ble_r.lock(|ble_r| {
-                        let l2cap = &mut *(ble_r.l2cap());
-                        let provider: &mut EnvironmentSensingService =
-                            l2cap.channel_mapper().attribute_provider();
-                        provider.set_temperature(f);
-                    });
-                },
-            );
-            thermometer.stop_measurement();
+        let seconds = uptime_ms.lock(|uptime_ms| {
+            *uptime_ms = uptime_ms.wrapping_add(RTC_PERIOD_MS);
+            *uptime_ms / 1000
+        });
+        // Each poll starts or reads a measurement. Report the latest average rather than the result
+        // of this poll, so that a failed read does not stop temperature updates.
+        thermometer.poll();
+        if *timer_count % 2 != 0 {
+            let millivolts = supply.measure_millivolts();
+            ble_r.lock(|ble_r| {
+                // Send console output that did not fit in the TX queue earlier
+                flush_console(ble_r);
+
+                let mut l2cap = ble_r.l2cap();
+                let notifications = {
+                    let services = l2cap.channel_mapper().attribute_provider();
+                    let ess: &mut EnvironmentSensingService = services.first().first().second();
+                    ess.update_time(seconds);
+                    if let Some(celsius) = thermometer.temperature() {
+                        ess.set_temperature_celsius(celsius);
+                    }
+                    let temperature = ess.take_notification();
+                    let battery: &mut BatteryService = services.first().second();
+                    battery.set_voltage(millivolts);
+                    [temperature, battery.take_notification()]
+                };
+                // Push the new values to subscribed clients, unless the TX queue is full
+                for notification in notifications.iter().flatten() {
+                    if let Some(mut att) = l2cap.att() {
+                        att.notify_raw(notification.handle(), notification.value());
+                    }
+                }
+            });
         }
         *timer_count += 1;
     }
@@ -211,14 +363,25 @@ const APP: () = {
         }
     }
 
-    #[task(resources = [ble_r], priority = 2)]
+    #[task(resources = [ble_r, console_line, uptime_ms], priority = 2)]
     fn ble_worker(ctx: ble_worker::Context) {
-        let ble_worker::Resources { ble_r } = ctx.resources;
+        let ble_worker::Resources {
+            ble_r,
+            console_line,
+            uptime_ms,
+        } = ctx.resources;
 
         // Fully drain the packet queue
         while ble_r.has_work() {
             ble_r.process_one().unwrap();
         }
+
+        console(
+            ble_r.l2cap().channel_mapper().attribute_provider(),
+            console_line,
+            *uptime_ms,
+        );
+        flush_console(ble_r);
     }
 
     extern "C" {
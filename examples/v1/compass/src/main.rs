@@ -0,0 +1,173 @@
+//! Example of a tilt-compensated compass, which is calibrated by filling the screen and then shows
+//! an arrow pointing north.
+#![no_main]
+#![no_std]
+
+#[allow(unused_imports)]
+use panic_halt;
+
+use drogue_microbit_accelerometer::Mma8653;
+use drogue_microbit_magnetometer::{Calibrator, Compass, Mag3110};
+use drogue_microbit_matrix::{Image, LedMatrix};
+
+use nrf51_hal as hal;
+
+use hal::gpio::{Level, Output, Pin, PushPull};
+use hal::rtc::{Rtc, RtcInterrupt};
+use log::LevelFilter;
+use rtic::app;
+use rtt_logger::RTTLogger;
+use rtt_target::rtt_init_print;
+use shared_bus::{CortexMMutex, I2cProxy};
+
+static LOGGER: RTTLogger = RTTLogger::new(LevelFilter::Debug);
+
+/// Arrows for each 45 degrees clockwise from the logo.
+static ARROWS: [Image; 8] = [
+    Image::ARROW_N,
+    Image::ARROW_NE,
+    Image::ARROW_E,
+    Image::ARROW_SE,
+    Image::ARROW_S,
+    Image::ARROW_SW,
+    Image::ARROW_W,
+    Image::ARROW_NW,
+];
+
+/// The accelerometer and magnetometer share the internal I2C bus
+type I2c = I2cProxy<'static, CortexMMutex<hal::Twi<hal::pac::TWI1>>>;
+
+type Led = LedMatrix<Pin<Output<PushPull>>>;
+
+#[app(device = crate::hal::pac, peripherals = true)]
+const APP: () = {
+    struct Resources {
+        rtc: Rtc<hal::pac::RTC0>,
+        display_rtc: Rtc<hal::pac::RTC1>,
+        led: Led,
+        accelerometer: Mma8653<I2c>,
+        magnetometer: Mag3110<I2c>,
+        calibrator: Calibrator,
+        compass: Option<Compass>,
+    }
+
+    #[init]
+    fn init(ctx: init::Context) -> init::LateResources {
+        rtt_init_print!();
+        log::set_max_level(log::LevelFilter::Debug);
+        unsafe {
+            log::set_logger_racy(&LOGGER).unwrap();
+        }
+
+        // The LED matrix takes most of port 0, so the pins are split here to keep the I2C bus
+        let port0 = hal::gpio::p0::Parts::new(ctx.device.GPIO);
+        let led = LedMatrix::from_pins(
+            [
+                port0.p0_13.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_14.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_15.into_push_pull_output(Level::Low).degrade(),
+            ],
+            [
+                port0.p0_04.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_05.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_06.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_07.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_08.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_09.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_10.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_11.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_12.into_push_pull_output(Level::Low).degrade(),
+            ],
+        );
+
+        let twi = hal::Twi::new(
+            ctx.device.TWI1,
+            hal::twi::Pins {
+                scl: port0.p0_00.into_floating_input().degrade(),
+                sda: port0.p0_30.into_floating_input().degrade(),
+            },
+            hal::twi::Frequency::K100,
+        );
+        let bus = shared_bus::new_cortexm!(hal::Twi<hal::pac::TWI1> = twi).unwrap();
+        let accelerometer = Mma8653::new(bus.acquire_i2c()).unwrap();
+        let magnetometer = Mag3110::new(bus.acquire_i2c()).unwrap();
+
+        let clocks = hal::clocks::Clocks::new(ctx.device.CLOCK).enable_ext_hfosc();
+        let _clocks = clocks.start_lfclk();
+
+        // Sample at 32768 / (655 + 1) = ~50 Hz, the rate of the accelerometer
+        let mut rtc = Rtc::new(ctx.device.RTC0, 655).unwrap();
+        rtc.enable_event(RtcInterrupt::Tick);
+        rtc.enable_counter();
+        rtc.enable_interrupt(RtcInterrupt::Tick, None);
+
+        // Refresh the display at 4096 Hz, which gives ~150 frames per second
+        let mut display_rtc = Rtc::new(ctx.device.RTC1, 7).unwrap();
+        display_rtc.enable_event(RtcInterrupt::Tick);
+        display_rtc.enable_counter();
+        display_rtc.enable_interrupt(RtcInterrupt::Tick, None);
+
+        log::info!("Tilt the micro:bit to fill the screen");
+
+        init::LateResources {
+            rtc: rtc,
+            display_rtc: display_rtc,
+            led: led,
+            accelerometer: accelerometer,
+            magnetometer: magnetometer,
+            calibrator: Calibrator::new(),
+            compass: None,
+        }
+    }
+
+    #[task(binds = RTC0, resources = [rtc, led, accelerometer, magnetometer, calibrator, compass])]
+    fn rtc0(ctx: rtc0::Context) {
+        let rtc0::Resources {
+            rtc,
+            mut led,
+            accelerometer,
+            magnetometer,
+            calibrator,
+            compass,
+        } = ctx.resources;
+
+        rtc.reset_event(RtcInterrupt::Tick);
+        rtc.clear_counter();
+
+        // The magnetometer samples at 10 Hz, so most reads return the previous sample
+        let (accel, field) = match (accelerometer.accel(), magnetometer.field_raw()) {
+            (Ok(accel), Ok(field)) => (accel, field),
+            _ => return,
+        };
+
+        let image = match compass {
+            Some(compass) => {
+                // North is at minus the heading from the logo
+                let heading = compass.heading(field, accel);
+                let arrow = ((360.0 - heading) / 45.0 + 0.5) as usize % ARROWS.len();
+                ARROWS[arrow]
+            }
+            None => {
+                if let Some(calibration) = calibrator.update(accel, field) {
+                    log::info!(
+                        "Calibrated with centre {:?} and radius {}",
+                        calibration.centre,
+                        calibration.radius
+                    );
+                    *compass = Some(Compass::new(calibration));
+                }
+                calibrator.image()
+            }
+        };
+        led.lock(|led| led.show(&image));
+    }
+
+    #[task(binds = RTC1, resources = [display_rtc, led], priority = 2)]
+    fn rtc1(ctx: rtc1::Context) {
+        let display_rtc: &mut Rtc<hal::pac::RTC1> = ctx.resources.display_rtc;
+        let led: &mut Led = ctx.resources.led;
+
+        display_rtc.reset_event(RtcInterrupt::Tick);
+        led.refresh();
+    }
+};
@@ -0,0 +1,202 @@
+//! Example of the micro:bit packet radio, exchanging numbers and strings with other micro:bits in
+//! the same radio group.
+#![no_main]
+#![no_std]
+
+#[allow(unused_imports)]
+use panic_halt;
+
+use drogue_microbit_buttons::{Button, Buttons, Event};
+use drogue_microbit_matrix::LedMatrix;
+use drogue_microbit_radio::{FrameBuffer, Packet, Payload, Radio, MAX_FRAME_LEN};
+use heapless::{consts, String};
+
+use nrf51_hal as hal;
+
+use core::fmt::Write;
+use hal::gpio::{Level, Output, Pin, PushPull};
+use hal::rtc::{Rtc, RtcInterrupt};
+use log::LevelFilter;
+use rtic::app;
+use rtt_logger::RTTLogger;
+use rtt_target::rtt_init_print;
+
+static LOGGER: RTTLogger = RTTLogger::new(LevelFilter::Debug);
+
+/// The radio group, which must match `radio.setGroup` in MakeCode
+const GROUP: u8 = 1;
+
+// RTC0 ticks at 32768 / (327 + 1) = 100 Hz
+const TICK_MS: u32 = 10;
+
+// Scroll one column every 150 ms
+const SCROLL_TICKS: u32 = 15;
+
+type Led = LedMatrix<Pin<Output<PushPull>>>;
+
+#[app(device = crate::hal::pac, peripherals = true)]
+const APP: () = {
+    struct Resources {
+        rtc: Rtc<hal::pac::RTC0>,
+        display_rtc: Rtc<hal::pac::RTC1>,
+        led: Led,
+        buttons: Buttons,
+        #[init(0)]
+        now_ms: u32,
+        #[init(0)]
+        count: i32,
+        #[init([0; 1 + MAX_FRAME_LEN])]
+        frame: FrameBuffer,
+        radio: Radio,
+    }
+
+    #[init(resources = [frame])]
+    fn init(ctx: init::Context) -> init::LateResources {
+        rtt_init_print!();
+        log::set_max_level(log::LevelFilter::Debug);
+        unsafe {
+            log::set_logger_racy(&LOGGER).unwrap();
+        }
+
+        // The LED matrix takes most of port 0, so the pins are split here to keep the buttons
+        let port0 = hal::gpio::p0::Parts::new(ctx.device.GPIO);
+        let led = LedMatrix::from_pins(
+            [
+                port0.p0_13.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_14.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_15.into_push_pull_output(Level::Low).degrade(),
+            ],
+            [
+                port0.p0_04.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_05.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_06.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_07.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_08.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_09.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_10.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_11.into_push_pull_output(Level::Low).degrade(),
+                port0.p0_12.into_push_pull_output(Level::Low).degrade(),
+            ],
+        );
+        let buttons = Buttons::new(
+            ctx.device.GPIOTE,
+            port0.p0_17.into_floating_input(),
+            port0.p0_26.into_floating_input(),
+        );
+
+        // The radio needs the crystal oscillator
+        let clocks = hal::clocks::Clocks::new(ctx.device.CLOCK).enable_ext_hfosc();
+        let _clocks = clocks.start_lfclk();
+
+        let mut radio = Radio::new(ctx.device.RADIO, ctx.resources.frame);
+        radio.set_group(GROUP);
+        radio.start_receive();
+
+        let mut rtc = Rtc::new(ctx.device.RTC0, 327).unwrap();
+        rtc.enable_event(RtcInterrupt::Tick);
+        rtc.enable_counter();
+        rtc.enable_interrupt(RtcInterrupt::Tick, None);
+
+        // Refresh the display at 4096 Hz, which gives ~150 frames per second
+        let mut display_rtc = Rtc::new(ctx.device.RTC1, 7).unwrap();
+        display_rtc.enable_event(RtcInterrupt::Tick);
+        display_rtc.enable_counter();
+        display_rtc.enable_interrupt(RtcInterrupt::Tick, None);
+
+        log::info!("Listening in radio group {}", GROUP);
+
+        init::LateResources {
+            rtc: rtc,
+            display_rtc: display_rtc,
+            led: led,
+            buttons: buttons,
+            radio: radio,
+        }
+    }
+
+    #[task(binds = RADIO, resources = [radio, led], priority = 2)]
+    fn radio(ctx: radio::Context) {
+        let radio::Resources { radio, mut led } = ctx.resources;
+        let datagram = match radio.on_interrupt() {
+            Some(datagram) => datagram,
+            None => return,
+        };
+        let packet = match datagram.packet() {
+            Ok(packet) => packet,
+            Err(e) => {
+                log::warn!("Invalid packet: {:?}", e);
+                return;
+            }
+        };
+        log::info!("Received {:?} at {} dBm", packet.payload, datagram.rssi);
+
+        let mut text: String<consts::U32> = String::new();
+        match &packet.payload {
+            Payload::Number(value) | Payload::Value { value, .. } => {
+                write!(text, "{}", value).ok();
+            }
+            Payload::String(value) => {
+                text.push_str(value).ok();
+            }
+            _ => {}
+        }
+        led.lock(|led| {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => led.show_char(c),
+                (Some(_), Some(_)) => led.scroll_text(&text, SCROLL_TICKS),
+                _ => {}
+            }
+        });
+    }
+
+    #[task(binds = GPIOTE, resources = [buttons, now_ms], priority = 1)]
+    fn gpiote(ctx: gpiote::Context) {
+        let gpiote::Resources { buttons, now_ms } = ctx.resources;
+        buttons.on_interrupt(*now_ms);
+    }
+
+    #[task(binds = RTC0, resources = [rtc, led, buttons, now_ms, count, radio], priority = 1)]
+    fn rtc0(ctx: rtc0::Context) {
+        let rtc0::Resources {
+            rtc,
+            mut led,
+            buttons,
+            now_ms,
+            count,
+            mut radio,
+        } = ctx.resources;
+        rtc.reset_event(RtcInterrupt::Tick);
+        rtc.clear_counter();
+
+        *now_ms = now_ms.wrapping_add(TICK_MS);
+        buttons.update(*now_ms);
+        while let Some(event) = buttons.next_event() {
+            let payload = match event {
+                Event::Click(Button::A) => {
+                    *count += 1;
+                    Payload::Number(*count)
+                }
+                Event::Click(Button::B) => Payload::string("hello"),
+                _ => continue,
+            };
+            let packet = Packet::new(*now_ms, 0, payload);
+            if let Err(e) = radio.lock(|radio| radio.send_packet(&packet)) {
+                log::warn!("Failed to send packet: {:?}", e);
+            }
+        }
+
+        led.lock(|led| {
+            led.scroll();
+        });
+    }
+
+    #[task(binds = RTC1, resources = [display_rtc, led], priority = 3)]
+    fn rtc1(ctx: rtc1::Context) {
+        let display_rtc: &mut Rtc<hal::pac::RTC1> = ctx.resources.display_rtc;
+        let led: &mut Led = ctx.resources.led;
+
+        display_rtc.reset_event(RtcInterrupt::Tick);
+        led.refresh();
+    }
+};
@@ -5,20 +5,38 @@
 #[allow(unused_imports)]
 use panic_semihosting;
 
-use drogue_microbit_matrix::LedMatrix;
+use drogue_microbit_matrix::{Animation, AnimationMode, AnimationPlayer, Frame, Image, LedMatrix};
 
+use hal::gpio::{Output, Pin, PushPull};
 use hal::rtc::{Rtc, RtcInterrupt};
 use rtic::app;
 use rtt_target::{rprintln, rtt_init_print};
 
 use nrf51_hal as hal;
 
+/// Clock hand going around once per second.
+static CLOCK: [Frame; 12] = [
+    Frame::new(Image::CLOCK12, 1),
+    Frame::new(Image::CLOCK1, 1),
+    Frame::new(Image::CLOCK2, 1),
+    Frame::new(Image::CLOCK3, 1),
+    Frame::new(Image::CLOCK4, 1),
+    Frame::new(Image::CLOCK5, 1),
+    Frame::new(Image::CLOCK6, 1),
+    Frame::new(Image::CLOCK7, 1),
+    Frame::new(Image::CLOCK8, 1),
+    Frame::new(Image::CLOCK9, 1),
+    Frame::new(Image::CLOCK10, 1),
+    Frame::new(Image::CLOCK11, 1),
+];
+
 #[app(device = crate::hal::pac, peripherals = true)]
 const APP: () = {
     struct Resources {
         rtc: Rtc<hal::pac::RTC0>,
-        led: LedMatrix,
-        count: usize,
+        display_rtc: Rtc<hal::pac::RTC1>,
+        led: LedMatrix<Pin<Output<PushPull>>>,
+        player: AnimationPlayer<'static>,
     }
 
     #[init]
@@ -26,38 +44,60 @@ const APP: () = {
         rtt_init_print!();
         let port0 = hal::gpio::p0::Parts::new(ctx.device.GPIO);
 
-        let led = LedMatrix::new(port0);
+        let mut led = LedMatrix::new(port0);
+
+        let player = AnimationPlayer::new(Animation::new(&CLOCK, AnimationMode::Loop));
+        if let Some(image) = player.current() {
+            led.show(image);
+        }
 
         let clocks = hal::clocks::Clocks::new(ctx.device.CLOCK).enable_ext_hfosc();
         let _clocks = clocks.start_lfclk();
 
-        let mut rtc = Rtc::new(ctx.device.RTC0, 4095).unwrap();
+        // Advance the animation at 12 Hz
+        let mut rtc = Rtc::new(ctx.device.RTC0, 2729).unwrap();
         rtc.enable_event(RtcInterrupt::Tick);
         rtc.enable_counter();
         rtc.enable_interrupt(RtcInterrupt::Tick, None);
 
+        // Refresh the display at 4096 Hz, which gives ~150 frames per second
+        let mut display_rtc = Rtc::new(ctx.device.RTC1, 7).unwrap();
+        display_rtc.enable_event(RtcInterrupt::Tick);
+        display_rtc.enable_counter();
+        display_rtc.enable_interrupt(RtcInterrupt::Tick, None);
+
         rprintln!("Started application");
 
         init::LateResources {
             rtc: rtc,
+            display_rtc: display_rtc,
             led: led,
-            count: 0,
+            player: player,
         }
     }
 
-    #[task(binds = RTC0, resources = [rtc, led, count])]
+    #[task(binds = RTC0, resources = [rtc, led, player])]
     fn rtc0(ctx: rtc0::Context) {
-        let rtc: &mut Rtc<hal::pac::RTC0> = ctx.resources.rtc;
-        let count: &mut usize = ctx.resources.count;
-        let led: &mut LedMatrix = ctx.resources.led;
+        let rtc0::Resources {
+            rtc,
+            mut led,
+            player,
+        } = ctx.resources;
 
         rtc.reset_event(RtcInterrupt::Tick);
         rtc.clear_counter();
 
-        let row = (*count / 5) % 5;
-        let col = *count % 5;
-        led.clear();
-        led.on(row, col);
-        *count += 1;
+        if let Some(image) = player.tick() {
+            led.lock(|led| led.show(image));
+        }
+    }
+
+    #[task(binds = RTC1, resources = [display_rtc, led], priority = 2)]
+    fn rtc1(ctx: rtc1::Context) {
+        let display_rtc: &mut Rtc<hal::pac::RTC1> = ctx.resources.display_rtc;
+        let led: &mut LedMatrix<Pin<Output<PushPull>>> = ctx.resources.led;
+
+        display_rtc.reset_event(RtcInterrupt::Tick);
+        led.refresh();
     }
 };
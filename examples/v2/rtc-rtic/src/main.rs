@@ -0,0 +1,104 @@
+//! Example showing the use of RTIC in combination with RTC and Led Matrix on the micro:bit v2.
+#![no_main]
+#![no_std]
+
+#[allow(unused_imports)]
+use panic_semihosting;
+
+use drogue_microbit_matrix::{Animation, AnimationMode, AnimationPlayer, Frame, Image, LedMatrix};
+
+use hal::gpio::{Output, Pin, PushPull};
+use hal::rtc::{Rtc, RtcInterrupt};
+use rtic::app;
+use rtt_target::{rprintln, rtt_init_print};
+
+use nrf52833_hal as hal;
+
+/// Clock hand going around once per second.
+static CLOCK: [Frame; 12] = [
+    Frame::new(Image::CLOCK12, 1),
+    Frame::new(Image::CLOCK1, 1),
+    Frame::new(Image::CLOCK2, 1),
+    Frame::new(Image::CLOCK3, 1),
+    Frame::new(Image::CLOCK4, 1),
+    Frame::new(Image::CLOCK5, 1),
+    Frame::new(Image::CLOCK6, 1),
+    Frame::new(Image::CLOCK7, 1),
+    Frame::new(Image::CLOCK8, 1),
+    Frame::new(Image::CLOCK9, 1),
+    Frame::new(Image::CLOCK10, 1),
+    Frame::new(Image::CLOCK11, 1),
+];
+
+#[app(device = crate::hal::pac, peripherals = true)]
+const APP: () = {
+    struct Resources {
+        rtc: Rtc<hal::pac::RTC0>,
+        display_rtc: Rtc<hal::pac::RTC1>,
+        led: LedMatrix<Pin<Output<PushPull>>>,
+        player: AnimationPlayer<'static>,
+    }
+
+    #[init]
+    fn init(ctx: init::Context) -> init::LateResources {
+        rtt_init_print!();
+        let port0 = hal::gpio::p0::Parts::new(ctx.device.P0);
+        let port1 = hal::gpio::p1::Parts::new(ctx.device.P1);
+
+        let mut led = LedMatrix::new(port0, port1);
+
+        let player = AnimationPlayer::new(Animation::new(&CLOCK, AnimationMode::Loop));
+        if let Some(image) = player.current() {
+            led.show(image);
+        }
+
+        let clocks = hal::clocks::Clocks::new(ctx.device.CLOCK).enable_ext_hfosc();
+        let _clocks = clocks.start_lfclk();
+
+        // Advance the animation at 12 Hz
+        let mut rtc = Rtc::new(ctx.device.RTC0, 2729).unwrap();
+        rtc.enable_event(RtcInterrupt::Tick);
+        rtc.enable_counter();
+        rtc.enable_interrupt(RtcInterrupt::Tick, None);
+
+        // Refresh the display at 8192 Hz, which gives ~180 frames per second
+        let mut display_rtc = Rtc::new(ctx.device.RTC1, 3).unwrap();
+        display_rtc.enable_event(RtcInterrupt::Tick);
+        display_rtc.enable_counter();
+        display_rtc.enable_interrupt(RtcInterrupt::Tick, None);
+
+        rprintln!("Started application");
+
+        init::LateResources {
+            rtc: rtc,
+            display_rtc: display_rtc,
+            led: led,
+            player: player,
+        }
+    }
+
+    #[task(binds = RTC0, resources = [rtc, led, player])]
+    fn rtc0(ctx: rtc0::Context) {
+        let rtc0::Resources {
+            rtc,
+            mut led,
+            player,
+        } = ctx.resources;
+
+        rtc.reset_event(RtcInterrupt::Tick);
+        rtc.clear_counter();
+
+        if let Some(image) = player.tick() {
+            led.lock(|led| led.show(image));
+        }
+    }
+
+    #[task(binds = RTC1, resources = [display_rtc, led], priority = 2)]
+    fn rtc1(ctx: rtc1::Context) {
+        let display_rtc: &mut Rtc<hal::pac::RTC1> = ctx.resources.display_rtc;
+        let led: &mut LedMatrix<Pin<Output<PushPull>>> = ctx.resources.led;
+
+        display_rtc.reset_event(RtcInterrupt::Tick);
+        led.refresh();
+    }
+};